@@ -52,4 +52,34 @@ pub enum DualProductError {
     
     #[msg("Invalid token ratio configuration")]
     InvalidRatios,
-}
\ No newline at end of file
+
+    #[msg("Account already migrated")]
+    AlreadyMigrated,
+
+    #[msg("Position too small to enter LP")]
+    PositionTooSmallForLP,
+
+    #[msg("Reward account mint does not match the configured reward mint")]
+    RewardMintMismatch,
+
+    #[msg("Operation is paused")]
+    OperationPaused,
+
+    #[msg("Invalid fee schedule")]
+    InvalidFeeSchedule,
+
+    #[msg("Combined deposit below minimum")]
+    BelowMinimumDeposit,
+
+    #[msg("Treasury cannot be the default pubkey")]
+    InvalidTreasury,
+
+    #[msg("Unsupported view response version")]
+    UnsupportedViewVersion,
+
+    #[msg("Protocol is globally paused")]
+    GloballyPaused,
+
+    #[msg("Pause flags set an undefined bit")]
+    InvalidPauseFlags,
+}
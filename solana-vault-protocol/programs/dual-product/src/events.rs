@@ -0,0 +1,7 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct TreasuryUpdated {
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+}
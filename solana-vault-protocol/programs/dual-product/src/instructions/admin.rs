@@ -1,6 +1,11 @@
 use anchor_lang::prelude::*;
-use crate::state::{DualProductConfig, DualConfig, DualPool};
+use anchor_lang::Discriminator;
+use anchor_spl::token::Mint;
+use crate::state::{DualProductConfig, DualConfig, DualPool, PoolState, PoolStateV1, FeeModel, FeeTier, MAX_FEE_TIERS, MAX_LOYALTY_DISCOUNT_BPS, PAUSE_FLAGS_MASK, REWARD_INDEX_SCALE};
 use crate::errors::DualProductError;
+use vault_sol::state::VaultConfig;
+use vault_math::per_second_rate;
+use crate::events::TreasuryUpdated;
 
 #[derive(Accounts)]
 pub struct InitializeProduct<'info> {
@@ -12,11 +17,33 @@ pub struct InitializeProduct<'info> {
         bump
     )]
     pub config: Account<'info, DualProductConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<PoolState>(),
+        seeds = [b"pool_state"],
+        bump
+    )]
+    pub pool_state: Account<'info, PoolState>,
+
+    // Protocol-wide fee limit
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
     
     pub treasury: SystemAccount<'info>,
+
+    // Mints that reward claims must pay out in
+    pub lst_reward_mint: Account<'info, Mint>,
+    pub usdc_reward_mint: Account<'info, Mint>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -33,6 +60,119 @@ pub struct UpdateRatios<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetFeeModel<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"dual_product_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, DualProductConfig>,
+
+    // Protocol-wide fee limit
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMinLPPositionValue<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"dual_product_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, DualProductConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDustThreshold<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"dual_product_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, DualProductConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardRates<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"dual_product_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, DualProductConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFeeSchedule<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"dual_product_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, DualProductConfig>,
+
+    // Protocol-wide fee limit
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeDiscountForLongTermStakers<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"dual_product_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, DualProductConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"dual_product_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, DualProductConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct PauseProduct<'info> {
     #[account(
@@ -71,6 +211,14 @@ pub struct InitializeDualProduct<'info> {
     )]
     pub config: Account<'info, DualConfig>,
 
+    // Protocol-wide fee limit
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+
     #[account(
         init,
         payer = authority,
@@ -105,6 +253,30 @@ pub struct UpdatePoolParameters<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"dual_config"],
+        bump = config.bump,
+        has_one = authority,
+    )]
+    pub config: Account<'info, DualConfig>,
+
+    // Withdrawal and claim fees are paid to this config's treasury
+    #[account(
+        mut,
+        seeds = [b"dual_product_config"],
+        bump = product_config.bump,
+        has_one = authority,
+    )]
+    pub product_config: Account<'info, DualProductConfig>,
+
+    pub new_treasury: SystemAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct PauseDualProduct<'info> {
     #[account(
@@ -118,6 +290,30 @@ pub struct PauseDualProduct<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct MigratePoolState<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"dual_product_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, DualProductConfig>,
+
+    /// CHECK: Deserialized manually from the pre-migration layout
+    #[account(
+        mut,
+        seeds = [b"pool_state"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub pool_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 pub fn initialize_product(
     ctx: Context<InitializeProduct>,
     platform_fee_bps: u16,
@@ -125,8 +321,12 @@ pub fn initialize_product(
     lst_ratio: u16,
     usdc_ratio: u16,
 ) -> Result<()> {
-    require!(platform_fee_bps <= 10000, DualProductError::InvalidAmount);
+    require!(platform_fee_bps <= ctx.accounts.vault_config.max_pool_fee_bps, DualProductError::InvalidFee);
     require!(min_deposit > 0, DualProductError::InvalidAmount);
+    require!(
+        ctx.accounts.treasury.key() != Pubkey::default(),
+        DualProductError::InvalidTreasury
+    );
     require!(
         lst_ratio + usdc_ratio == 10000,
         DualProductError::InvalidRatios
@@ -141,9 +341,31 @@ pub fn initialize_product(
     config.min_deposit_amount = min_deposit;
     config.lst_ratio = lst_ratio;
     config.usdc_ratio = usdc_ratio;
+    config.fee_model = FeeModel::FlatWithdrawal { fee_bps: platform_fee_bps };
+    config.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+    config.fee_tier_count = 0;
+    config.loyalty_min_tenure_days = 0;
+    config.loyalty_discount_bps = 0;
+    config.min_lp_position_value = 0;
+    config.dust_threshold = 1000;
+    config.lst_reward_mint = ctx.accounts.lst_reward_mint.key();
+    config.usdc_reward_mint = ctx.accounts.usdc_reward_mint.key();
+    config.lst_decimals = ctx.accounts.lst_reward_mint.decimals;
+    config.usdc_decimals = ctx.accounts.usdc_reward_mint.decimals;
     config.paused = false;
+    config.pause_flags = 0;
     config.bump = bump;
 
+    // Reward rates start at zero until set_reward_rates
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.total_lst = 0;
+    pool_state.total_usdc = 0;
+    pool_state.total_shares = 0;
+    pool_state.lst_per_share = 0;
+    pool_state.usdc_per_share = 0;
+    pool_state.last_update = Clock::get()?.unix_timestamp;
+    pool_state.bump = *ctx.bumps.get("pool_state").unwrap();
+
     Ok(())
 }
 
@@ -164,6 +386,113 @@ pub fn update_ratios(
     Ok(())
 }
 
+pub fn set_fee_model(
+    ctx: Context<SetFeeModel>,
+    fee_model: FeeModel,
+) -> Result<()> {
+    let fee_bps = match fee_model {
+        FeeModel::FlatWithdrawal { fee_bps } | FeeModel::ProfitOnly { fee_bps } => fee_bps,
+    };
+    require!(fee_bps <= ctx.accounts.vault_config.max_pool_fee_bps, DualProductError::InvalidFee);
+
+    let config = &mut ctx.accounts.config;
+    config.fee_model = fee_model;
+
+    Ok(())
+}
+
+pub fn set_min_lp_position_value(
+    ctx: Context<SetMinLPPositionValue>,
+    min_lp_position_value: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.min_lp_position_value = min_lp_position_value;
+
+    Ok(())
+}
+
+pub fn set_dust_threshold(
+    ctx: Context<SetDustThreshold>,
+    dust_threshold: u64,
+) -> Result<()> {
+    require!(
+        (1..=1_000_000).contains(&dust_threshold),
+        DualProductError::InvalidAmount
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.dust_threshold = dust_threshold;
+
+    Ok(())
+}
+
+// Sets the LST and USDC reward indexes from APYs in basis points; claims pay
+// them per unit held for every second since the last claim
+pub fn set_reward_rates(
+    ctx: Context<SetRewardRates>,
+    lst_apy_points: u16,
+    usdc_apy_points: u16,
+) -> Result<()> {
+    require!(
+        lst_apy_points <= 10000 && usdc_apy_points <= 10000,
+        DualProductError::InvalidAmount
+    );
+
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.lst_per_share = per_second_rate(lst_apy_points, REWARD_INDEX_SCALE)
+        .ok_or(DualProductError::MathOverflow)?;
+    pool_state.usdc_per_share = per_second_rate(usdc_apy_points, REWARD_INDEX_SCALE)
+        .ok_or(DualProductError::MathOverflow)?;
+
+    Ok(())
+}
+
+pub fn set_protocol_fee_schedule(
+    ctx: Context<SetProtocolFeeSchedule>,
+    tiers: Vec<FeeTier>,
+) -> Result<()> {
+    require!(tiers.len() <= MAX_FEE_TIERS, DualProductError::InvalidFeeSchedule);
+    for (i, tier) in tiers.iter().enumerate() {
+        require!(tier.fee_bps <= ctx.accounts.vault_config.max_pool_fee_bps, DualProductError::InvalidFee);
+        // The first matching tier wins, so thresholds must be strictly descending
+        if i > 0 {
+            require!(
+                tier.volume_threshold < tiers[i - 1].volume_threshold,
+                DualProductError::InvalidFeeSchedule
+            );
+        }
+    }
+
+    let config = &mut ctx.accounts.config;
+    config.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+    config.fee_tiers[..tiers.len()].copy_from_slice(&tiers);
+    config.fee_tier_count = tiers.len() as u8;
+
+    Ok(())
+}
+
+pub fn set_fee_discount_for_long_term_stakers(
+    ctx: Context<SetFeeDiscountForLongTermStakers>,
+    min_tenure_days: u32,
+    discount_bps: u16,
+) -> Result<()> {
+    require!(discount_bps <= MAX_LOYALTY_DISCOUNT_BPS, DualProductError::InvalidFee);
+
+    let config = &mut ctx.accounts.config;
+    config.loyalty_min_tenure_days = min_tenure_days;
+    config.loyalty_discount_bps = discount_bps;
+
+    Ok(())
+}
+
+pub fn set_pause_flags(ctx: Context<SetPauseFlags>, flags: u8) -> Result<()> {
+    require!(flags & !PAUSE_FLAGS_MASK == 0, DualProductError::InvalidPauseFlags);
+
+    let config = &mut ctx.accounts.config;
+    config.pause_flags = flags;
+    Ok(())
+}
+
 pub fn pause_product(ctx: Context<PauseProduct>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = true;
@@ -181,7 +510,7 @@ pub fn initialize_dual_product(
     platform_fee_bps: u16,
     min_dual_amount: u64,
 ) -> Result<()> {
-    require!(platform_fee_bps <= 1000, DualProductError::InvalidFee); // Max 10% fee
+    require!(platform_fee_bps <= ctx.accounts.vault_config.max_pool_fee_bps, DualProductError::InvalidFee);
 
     let config = &mut ctx.accounts.config;
     let pool = &mut ctx.accounts.pool;
@@ -193,7 +522,7 @@ pub fn initialize_dual_product(
     config.total_dual_positions = 0;
     config.users_count = 0;
     config.paused = false;
-    config.bump = *ctx.bumps.get("dual_config").unwrap();
+    config.bump = *ctx.bumps.get("config").unwrap();
 
     pool.total_wsol = 0;
     pool.total_usdc = 0;
@@ -206,7 +535,7 @@ pub fn initialize_dual_product(
     pool.tier3_multiplier = 20000; // 2x
     pool.last_update = Clock::get()?.unix_timestamp;
     pool.rewards_available = 0;
-    pool.bump = *ctx.bumps.get("dual_pool").unwrap();
+    pool.bump = *ctx.bumps.get("pool").unwrap();
 
     Ok(())
 }
@@ -244,6 +573,25 @@ pub fn update_pool_parameters(
     Ok(())
 }
 
+pub fn set_treasury(ctx: Context<SetTreasury>) -> Result<()> {
+    require!(
+        ctx.accounts.new_treasury.key() != Pubkey::default(),
+        DualProductError::InvalidTreasury
+    );
+
+    let config = &mut ctx.accounts.config;
+    let old_treasury = config.treasury;
+    config.treasury = ctx.accounts.new_treasury.key();
+    ctx.accounts.product_config.treasury = config.treasury;
+
+    emit!(TreasuryUpdated {
+        old_treasury,
+        new_treasury: config.treasury,
+    });
+
+    Ok(())
+}
+
 pub fn pause_dual_product(ctx: Context<PauseDualProduct>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = true;
@@ -254,4 +602,61 @@ pub fn unpause_dual_product(ctx: Context<PauseDualProduct>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = false;
     Ok(())
-}
\ No newline at end of file
+}
+
+// Rewrites a PoolState with u64 1e9-scaled reward indexes into the current
+// u128 1e18-scaled layout, topping up rent for the larger account
+pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+    let pool_info = ctx.accounts.pool_state.to_account_info();
+    let new_space = 8 + std::mem::size_of::<PoolState>();
+
+    let legacy = {
+        let data = pool_info.try_borrow_data()?;
+        require!(data.len() < new_space, DualProductError::AlreadyMigrated);
+        require!(
+            data.len() >= 8 && data[..8] == PoolState::discriminator(),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        PoolStateV1::deserialize(&mut &data[8..])?
+    };
+
+    // Fund rent for the extra space
+    let required_lamports = Rent::get()?.minimum_balance(new_space);
+    let shortfall = required_lamports.saturating_sub(pool_info.lamports());
+    if shortfall > 0 {
+        anchor_lang::solana_program::program::invoke(
+            &anchor_lang::solana_program::system_instruction::transfer(
+                ctx.accounts.authority.key,
+                pool_info.key,
+                shortfall,
+            ),
+            &[
+                ctx.accounts.authority.to_account_info(),
+                pool_info.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    pool_info.realloc(new_space, false)?;
+
+    // Rescale indexes from 1e9 to 1e18
+    let migrated = PoolState {
+        total_lst: legacy.total_lst,
+        total_usdc: legacy.total_usdc,
+        total_shares: legacy.total_shares,
+        lst_per_share: (legacy.lst_per_share as u128)
+            .checked_mul(1_000_000_000)
+            .ok_or(DualProductError::MathOverflow)?,
+        usdc_per_share: (legacy.usdc_per_share as u128)
+            .checked_mul(1_000_000_000)
+            .ok_or(DualProductError::MathOverflow)?,
+        last_update: legacy.last_update,
+        bump: legacy.bump,
+    };
+
+    let mut data = pool_info.try_borrow_mut_data()?;
+    migrated.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
@@ -3,8 +3,11 @@ use anchor_spl::{
     token::{Token, TokenAccount, Mint, Transfer},
     associated_token::AssociatedToken,
 };
-use crate::state::{DualConfig, DualPool, DualPosition};
+use crate::state::{DualConfig, DualProductConfig, DualPool, DualPosition, UserDualPosition, PoolState, UserLoyaltyProfile, UserVolumeTracker, DEPOSIT_BIT};
 use crate::errors::DualProductError;
+use vault_sol::state::GlobalPause;
+use crate::instructions::rewards::{normalize_amount, normalized_value};
+use crate::instructions::withdrawals::{add_volume, decayed_volume};
 
 #[derive(Accounts)]
 pub struct CreateDualPosition<'info> {
@@ -15,6 +18,22 @@ pub struct CreateDualPosition<'info> {
     )]
     pub config: Account<'info, DualConfig>,
 
+    // Deposits honor the per-operation pause flags on the product config
+    #[account(
+        seeds = [b"dual_product_config"],
+        bump = product_config.bump,
+        constraint = product_config.pause_flags & (1 << DEPOSIT_BIT) == 0 @ DualProductError::OperationPaused,
+    )]
+    pub product_config: Account<'info, DualProductConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ DualProductError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"dual_pool"],
@@ -31,23 +50,33 @@ pub struct CreateDualPosition<'info> {
     )]
     pub user_position: Account<'info, DualPosition>,
 
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + std::mem::size_of::<UserLoyaltyProfile>(),
+        seeds = [b"loyalty", user.key().as_ref()],
+        bump
+    )]
+    pub loyalty_profile: Account<'info, UserLoyaltyProfile>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
     // WSOL token account
-    #[account(mut)]
+    #[account(mut, constraint = user_wsol_account.mint == wsol_mint.key() @ DualProductError::InvalidTokenMint)]
     pub user_wsol_account: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(mut, constraint = pool_wsol_account.mint == wsol_mint.key() @ DualProductError::InvalidTokenMint)]
     pub pool_wsol_account: Account<'info, TokenAccount>,
 
     // USDC token account
-    #[account(mut)]
+    #[account(mut, constraint = user_usdc_account.mint == usdc_mint.key() @ DualProductError::InvalidTokenMint)]
     pub user_usdc_account: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(mut, constraint = pool_usdc_account.mint == usdc_mint.key() @ DualProductError::InvalidTokenMint)]
     pub pool_usdc_account: Account<'info, TokenAccount>,
 
+    // Mint decimals value both legs against min_dual_amount
     pub wsol_mint: Account<'info, Mint>,
     pub usdc_mint: Account<'info, Mint>,
     
@@ -65,6 +94,22 @@ pub struct AddToPosition<'info> {
     )]
     pub config: Account<'info, DualConfig>,
 
+    // Deposits honor the per-operation pause flags on the product config
+    #[account(
+        seeds = [b"dual_product_config"],
+        bump = product_config.bump,
+        constraint = product_config.pause_flags & (1 << DEPOSIT_BIT) == 0 @ DualProductError::OperationPaused,
+    )]
+    pub product_config: Account<'info, DualProductConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ DualProductError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"dual_pool"],
@@ -80,22 +125,123 @@ pub struct AddToPosition<'info> {
     )]
     pub user_position: Account<'info, DualPosition>,
 
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + std::mem::size_of::<UserLoyaltyProfile>(),
+        seeds = [b"loyalty", user.key().as_ref()],
+        bump
+    )]
+    pub loyalty_profile: Account<'info, UserLoyaltyProfile>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut)]
+    #[account(mut, constraint = user_wsol_account.mint == wsol_mint.key() @ DualProductError::InvalidTokenMint)]
     pub user_wsol_account: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(mut, constraint = pool_wsol_account.mint == wsol_mint.key() @ DualProductError::InvalidTokenMint)]
     pub pool_wsol_account: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(mut, constraint = user_usdc_account.mint == usdc_mint.key() @ DualProductError::InvalidTokenMint)]
     pub user_usdc_account: Account<'info, TokenAccount>,
     
-    #[account(mut)]
+    #[account(mut, constraint = pool_usdc_account.mint == usdc_mint.key() @ DualProductError::InvalidTokenMint)]
     pub pool_usdc_account: Account<'info, TokenAccount>,
 
+    // Mint decimals value both legs against min_dual_amount
+    pub wsol_mint: Account<'info, Mint>,
+    pub usdc_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositDual<'info> {
+    #[account(
+        seeds = [b"dual_product_config"],
+        bump = config.bump,
+        constraint = !config.paused @ DualProductError::ProductPaused,
+        constraint = config.pause_flags & (1 << DEPOSIT_BIT) == 0 @ DualProductError::OperationPaused,
+    )]
+    pub config: Account<'info, DualProductConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ DualProductError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + std::mem::size_of::<UserDualPosition>(),
+        seeds = [b"user_dual_position", user.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserDualPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + std::mem::size_of::<UserLoyaltyProfile>(),
+        seeds = [b"loyalty", user.key().as_ref()],
+        bump
+    )]
+    pub loyalty_profile: Account<'info, UserLoyaltyProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + std::mem::size_of::<UserVolumeTracker>(),
+        seeds = [b"user_volume_tracker", user.key().as_ref()],
+        bump
+    )]
+    pub volume_tracker: Account<'info, UserVolumeTracker>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // LST Token accounts; the vault side is held by the config PDA
+    #[account(
+        mut,
+        constraint = user_lst_account.owner == user.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = user_lst_account.mint == config.lst_reward_mint @ DualProductError::InvalidTokenMint,
+    )]
+    pub user_lst_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = vault_lst_account.owner == config.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = vault_lst_account.mint == config.lst_reward_mint @ DualProductError::InvalidTokenMint,
+    )]
+    pub vault_lst_account: Account<'info, TokenAccount>,
+
+    // USDC Token accounts; the vault side is held by the config PDA
+    #[account(
+        mut,
+        constraint = user_usdc_account.owner == user.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = user_usdc_account.mint == config.usdc_reward_mint @ DualProductError::InvalidTokenMint,
+    )]
+    pub user_usdc_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = vault_usdc_account.owner == config.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = vault_usdc_account.mint == config.usdc_reward_mint @ DualProductError::InvalidTokenMint,
+    )]
+    pub vault_usdc_account: Account<'info, TokenAccount>,
+
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn create_dual_position(
@@ -105,9 +251,17 @@ pub fn create_dual_position(
 ) -> Result<()> {
     let config = &mut ctx.accounts.config;
     require!(!config.paused, DualProductError::ProductPaused);
+    // Minimum applies to the combined deposit so single-leg deposits are
+    // held to the same bar
+    let combined_value = combined_deposit_value(
+        wsol_amount,
+        ctx.accounts.wsol_mint.decimals,
+        usdc_amount,
+        ctx.accounts.usdc_mint.decimals,
+    )?;
     require!(
-        wsol_amount >= config.min_dual_amount,
-        DualProductError::BelowMinimumAmount
+        combined_value >= config.min_dual_amount as u128,
+        DualProductError::BelowMinimumDeposit
     );
 
     // Transfer WSOL
@@ -146,6 +300,14 @@ pub fn create_dual_position(
     user_position.last_reward_claim = Clock::get()?.unix_timestamp;
     user_position.bump = *ctx.bumps.get("user_position").unwrap();
 
+    let loyalty_bump = *ctx.bumps.get("loyalty_profile").unwrap();
+    refresh_loyalty_profile(
+        &mut ctx.accounts.loyalty_profile,
+        ctx.accounts.user.key(),
+        loyalty_bump,
+        Clock::get()?.unix_timestamp,
+    );
+
     // Update config
     config.total_dual_positions = config.total_dual_positions.checked_add(1).unwrap();
     config.users_count = config.users_count.checked_add(1).unwrap();
@@ -160,6 +322,17 @@ pub fn add_to_position(
 ) -> Result<()> {
     let config = &ctx.accounts.config;
     require!(!config.paused, DualProductError::ProductPaused);
+    // Top-ups are held to the same minimum as the opening deposit
+    let combined_value = combined_deposit_value(
+        wsol_amount,
+        ctx.accounts.wsol_mint.decimals,
+        usdc_amount,
+        ctx.accounts.usdc_mint.decimals,
+    )?;
+    require!(
+        combined_value >= config.min_dual_amount as u128,
+        DualProductError::BelowMinimumDeposit
+    );
 
     // Transfer WSOL
     let wsol_transfer_ctx = CpiContext::new(
@@ -193,5 +366,147 @@ pub fn add_to_position(
     user_position.wsol_amount = user_position.wsol_amount.checked_add(wsol_amount).unwrap();
     user_position.usdc_amount = user_position.usdc_amount.checked_add(usdc_amount).unwrap();
 
+    let loyalty_bump = *ctx.bumps.get("loyalty_profile").unwrap();
+    refresh_loyalty_profile(
+        &mut ctx.accounts.loyalty_profile,
+        ctx.accounts.user.key(),
+        loyalty_bump,
+        Clock::get()?.unix_timestamp,
+    );
+
     Ok(())
-}
\ No newline at end of file
+}
+
+// Opens the user's LST + USDC position, held in vault accounts owned by the
+// product config. A position is funded once and closed by withdraw_dual once
+// emptied
+pub fn deposit_dual(
+    ctx: Context<DepositDual>,
+    lst_amount: u64,
+    usdc_amount: u64,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(
+        normalized_value(config, lst_amount, usdc_amount)? >= config.min_deposit_amount as u128,
+        DualProductError::BelowMinimumDeposit
+    );
+
+    if lst_amount > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_lst_account.to_account_info(),
+                    to: ctx.accounts.vault_lst_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            lst_amount,
+        )?;
+    }
+
+    if usdc_amount > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_usdc_account.to_account_info(),
+                    to: ctx.accounts.vault_usdc_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            usdc_amount,
+        )?;
+    }
+
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.total_lst = pool_state.total_lst
+        .checked_add(lst_amount)
+        .ok_or(DualProductError::MathOverflow)?;
+    pool_state.total_usdc = pool_state.total_usdc
+        .checked_add(usdc_amount)
+        .ok_or(DualProductError::MathOverflow)?;
+    pool_state.last_update = current_time;
+
+    let user_position = &mut ctx.accounts.user_position;
+    user_position.owner = ctx.accounts.user.key();
+    user_position.lst_amount = lst_amount;
+    user_position.usdc_amount = usdc_amount;
+    user_position.lst_principal = lst_amount;
+    user_position.usdc_principal = usdc_amount;
+    user_position.in_lp = false;
+    user_position.deposit_timestamp = current_time;
+    user_position.last_reward_claim = current_time;
+    user_position.bump = *ctx.bumps.get("user_position").unwrap();
+
+    let loyalty_bump = *ctx.bumps.get("loyalty_profile").unwrap();
+    refresh_loyalty_profile(
+        &mut ctx.accounts.loyalty_profile,
+        ctx.accounts.user.key(),
+        loyalty_bump,
+        current_time,
+    );
+
+    // Deposits count toward the rolling volume that selects the fee tier
+    let volume_tracker = &mut ctx.accounts.volume_tracker;
+    if volume_tracker.user == Pubkey::default() {
+        volume_tracker.user = ctx.accounts.user.key();
+        volume_tracker.bump = *ctx.bumps.get("volume_tracker").unwrap();
+    }
+    let current_slot = Clock::get()?.slot;
+    let rolling_volume = decayed_volume(volume_tracker, current_slot)?;
+    volume_tracker.rolling_30d_volume = add_volume(config, rolling_volume, lst_amount, usdc_amount)?;
+    volume_tracker.last_update_slot = current_slot;
+
+    Ok(())
+}
+
+// Value of a WSOL + USDC deposit with both legs scaled to the larger of the
+// two mint decimals
+fn combined_deposit_value(
+    wsol_amount: u64,
+    wsol_decimals: u8,
+    usdc_amount: u64,
+    usdc_decimals: u8,
+) -> Result<u128> {
+    let common_decimals = wsol_decimals.max(usdc_decimals);
+
+    Ok(normalize_amount(wsol_amount, wsol_decimals, common_decimals)?
+        .checked_add(normalize_amount(usdc_amount, usdc_decimals, common_decimals)?)
+        .ok_or(DualProductError::MathOverflow)?)
+}
+
+// Starts the tenure clock on a user's first stake and recomputes the
+// number of whole days staked since
+pub fn refresh_loyalty_profile(
+    loyalty_profile: &mut UserLoyaltyProfile,
+    user: Pubkey,
+    bump: u8,
+    current_time: i64,
+) {
+    if loyalty_profile.user == Pubkey::default() {
+        loyalty_profile.user = user;
+        loyalty_profile.bump = bump;
+    }
+    if loyalty_profile.first_stake_timestamp == 0 {
+        loyalty_profile.first_stake_timestamp = current_time;
+    }
+
+    let tenure_seconds = current_time.saturating_sub(loyalty_profile.first_stake_timestamp);
+    loyalty_profile.consecutive_staking_days = (tenure_seconds / 86400) as u32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deposit_value_scales_both_legs_to_the_larger_decimals() {
+        // One whole WSOL and one whole USDC are worth the same
+        assert_eq!(combined_deposit_value(1_000_000_000, 9, 0, 6).unwrap(), 1_000_000_000);
+        assert_eq!(combined_deposit_value(0, 9, 1_000_000, 6).unwrap(), 1_000_000_000);
+        assert_eq!(combined_deposit_value(1, 9, 1, 6).unwrap(), 1_001);
+    }
+}
@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
-use crate::state::{DualProductConfig, UserDualPosition, PoolState};
+use crate::state::{DualProductConfig, UserDualPosition, PoolState, DEPOSIT_BIT, WITHDRAWAL_BIT};
 use crate::errors::DualProductError;
+use vault_sol::state::GlobalPause;
+use crate::instructions::rewards::normalized_value;
 
 #[derive(Accounts)]
 pub struct AddToLP<'info> {
@@ -8,9 +10,18 @@ pub struct AddToLP<'info> {
         seeds = [b"dual_product_config"],
         bump = config.bump,
         constraint = !config.paused @ DualProductError::ProductPaused,
+        constraint = config.pause_flags & (1 << DEPOSIT_BIT) == 0 @ DualProductError::OperationPaused,
     )]
     pub config: Account<'info, DualProductConfig>,
 
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ DualProductError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"user_dual_position", user.key().as_ref()],
@@ -40,9 +51,18 @@ pub struct RemoveFromLP<'info> {
         seeds = [b"dual_product_config"],
         bump = config.bump,
         constraint = !config.paused @ DualProductError::ProductPaused,
+        constraint = config.pause_flags & (1 << WITHDRAWAL_BIT) == 0 @ DualProductError::OperationPaused,
     )]
     pub config: Account<'info, DualProductConfig>,
 
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ DualProductError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"user_dual_position", user.key().as_ref()],
@@ -67,9 +87,18 @@ pub struct RemoveFromLP<'info> {
 }
 
 pub fn add_to_lp(ctx: Context<AddToLP>) -> Result<()> {
+    let config = &ctx.accounts.config;
     let user_position = &mut ctx.accounts.user_position;
     let pool_state = &mut ctx.accounts.pool_state;
 
+    // Reject positions too small for the AMM; both legs are brought to a
+    // common decimal scale as there is no price feed
+    let position_value = normalized_value(config, user_position.lst_amount, user_position.usdc_amount)?;
+    require!(
+        position_value >= config.min_lp_position_value as u128,
+        DualProductError::PositionTooSmallForLP
+    );
+
     // Calculate shares to mint based on contribution
     let share_amount = if pool_state.total_shares == 0 {
         // Initial liquidity provision
@@ -117,4 +146,4 @@ pub fn remove_from_lp(ctx: Context<RemoveFromLP>) -> Result<()> {
     user_position.in_lp = false;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,7 +1,13 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
-use crate::state::{DualProductConfig, UserDualPosition, PoolState, RewardSource};
+use crate::state::{DualProductConfig, UserDualPosition, PoolState, RewardSource, REWARD_INDEX_SCALE, REWARD_CLAIM_BIT};
 use crate::errors::DualProductError;
+use vault_sol::state::{GlobalPause, VaultConfig};
+use vault_sol::utils::is_fee_exempt;
+use vault_math::apply_bps;
+use crate::view::PREVIEW_DUAL_REWARDS_VIEW_VERSION;
+use anchor_lang::solana_program::program::set_return_data;
+use vault_math::view::encode_view_response;
 
 #[derive(Accounts)]
 pub struct ClaimDualRewards<'info> {
@@ -9,9 +15,26 @@ pub struct ClaimDualRewards<'info> {
         seeds = [b"dual_product_config"],
         bump = config.bump,
         constraint = !config.paused @ DualProductError::ProductPaused,
+        constraint = config.pause_flags & (1 << REWARD_CLAIM_BIT) == 0 @ DualProductError::OperationPaused,
     )]
     pub config: Account<'info, DualProductConfig>,
 
+    // Protocol-wide fee exemption list
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ DualProductError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"user_dual_position", user.key().as_ref()],
@@ -30,26 +53,89 @@ pub struct ClaimDualRewards<'info> {
     pub user: Signer<'info>,
     
     // LST reward token accounts
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_lst_reward_account.owner == user.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = user_lst_reward_account.mint == config.lst_reward_mint @ DualProductError::RewardMintMismatch,
+    )]
     pub user_lst_reward_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = vault_lst_reward_account.owner == config.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = vault_lst_reward_account.mint == config.lst_reward_mint @ DualProductError::RewardMintMismatch,
+    )]
     pub vault_lst_reward_account: Account<'info, TokenAccount>,
     
     // USDC reward token accounts
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_usdc_reward_account.owner == user.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = user_usdc_reward_account.mint == config.usdc_reward_mint @ DualProductError::RewardMintMismatch,
+    )]
     pub user_usdc_reward_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = vault_usdc_reward_account.owner == config.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = vault_usdc_reward_account.mint == config.usdc_reward_mint @ DualProductError::RewardMintMismatch,
+    )]
     pub vault_usdc_reward_account: Account<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub treasury: SystemAccount<'info>,
+    // Platform fees go to the configured treasury
+    #[account(
+        mut,
+        constraint = treasury_lst_account.owner == config.treasury @ DualProductError::InvalidTokenAccountOwner,
+        constraint = treasury_lst_account.mint == config.lst_reward_mint @ DualProductError::RewardMintMismatch,
+    )]
+    pub treasury_lst_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_usdc_account.owner == config.treasury @ DualProductError::InvalidTokenAccountOwner,
+        constraint = treasury_usdc_account.mint == config.usdc_reward_mint @ DualProductError::RewardMintMismatch,
+    )]
+    pub treasury_usdc_account: Account<'info, TokenAccount>,
     
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct PreviewDualRewards<'info> {
+    #[account(
+        seeds = [b"dual_product_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, DualProductConfig>,
+
+    // Protocol-wide fee exemption list
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"user_dual_position", user_position.owner.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserDualPosition>,
+
+    #[account(
+        seeds = [b"pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, PoolState>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct DualRewardsPreview {
+    pub lst_amount: u64,
+    pub usdc_amount: u64,
+}
+
 pub fn claim_dual_rewards(
     ctx: Context<ClaimDualRewards>,
     reward_source: RewardSource,
+    max_claim_amount: Option<u64>,
 ) -> Result<()> {
     let config = &ctx.accounts.config;
     let user_position = &mut ctx.accounts.user_position;
@@ -63,230 +149,366 @@ pub fn claim_dual_rewards(
     
     require!(time_staked > 0, DualProductError::InvalidAmount);
 
-    match reward_source {
-        RewardSource::LST => {
-            // Calculate LST rewards based on staking duration
-            let lst_rewards = calculate_lst_rewards(
-                user_position.lst_amount,
-                time_staked,
-                pool_state.lst_per_share,
-            )?;
-
-            // Apply platform fee
-            let lst_fee = (lst_rewards as u128)
-                .checked_mul(config.platform_fee_bps as u128)
-                .ok_or(DualProductError::MathOverflow)?
-                .checked_div(10000)
-                .ok_or(DualProductError::MathOverflow)? as u64;
-
-            let lst_to_user = lst_rewards.checked_sub(lst_fee)
-                .ok_or(DualProductError::MathOverflow)?;
+    // Same path as preview_dual_rewards, so the preview matches the payout
+    let (lst_net, usdc_net) = pending_dual_rewards(
+        config,
+        user_position,
+        pool_state,
+        reward_source,
+        current_time,
+        platform_fee_bps_for(config, &ctx.accounts.vault_config, ctx.accounts.user.key),
+    )?;
+    let (lst_gross, usdc_gross) = pending_dual_rewards(
+        config,
+        user_position,
+        pool_state,
+        reward_source,
+        current_time,
+        0,
+    )?;
+
+    let (lst_to_user, usdc_to_user, next_claim_timestamp) = cap_claim(
+        config,
+        lst_net,
+        usdc_net,
+        user_position.last_reward_claim,
+        current_time,
+        max_claim_amount,
+    )?;
+    let lst_fee = claimed_fee(lst_gross, lst_net, lst_to_user)?;
+    let usdc_fee = claimed_fee(usdc_gross, usdc_net, usdc_to_user)?;
+
+    // Leave dust, including a capped claim worth less than the threshold,
+    // to keep accruing instead of paying it out
+    if is_dust(config, lst_to_user, usdc_to_user)? {
+        return Ok(());
+    }
 
-            // Transfer LST rewards
-            anchor_spl::token::transfer(
-                CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
-                    anchor_spl::token::Transfer {
-                        from: ctx.accounts.vault_lst_reward_account.to_account_info(),
-                        to: ctx.accounts.user_lst_reward_account.to_account_info(),
-                        authority: config.to_account_info(),
-                    },
-                ),
-                lst_to_user,
-            )?;
-        },
-        RewardSource::LP => {
-            require!(user_position.in_lp, DualProductError::PositionNotInLP);
-            
-            // Calculate LP rewards
-            let lp_rewards = calculate_lp_rewards(
-                user_position.lst_amount,
-                user_position.usdc_amount,
-                time_staked,
-                pool_state,
-            )?;
-
-            // Split rewards into LST and USDC components
-            let (lst_lp_rewards, usdc_lp_rewards) = lp_rewards;
-
-            // Apply platform fees
-            let lst_fee = (lst_lp_rewards as u128)
-                .checked_mul(config.platform_fee_bps as u128)
-                .ok_or(DualProductError::MathOverflow)?
-                .checked_div(10000)
-                .ok_or(DualProductError::MathOverflow)? as u64;
-
-            let usdc_fee = (usdc_lp_rewards as u128)
-                .checked_mul(config.platform_fee_bps as u128)
-                .ok_or(DualProductError::MathOverflow)?
-                .checked_div(10000)
-                .ok_or(DualProductError::MathOverflow)? as u64;
-
-            let lst_to_user = lst_lp_rewards.checked_sub(lst_fee)
-                .ok_or(DualProductError::MathOverflow)?;
-            let usdc_to_user = usdc_lp_rewards.checked_sub(usdc_fee)
-                .ok_or(DualProductError::MathOverflow)?;
+    // Transfer LST rewards, signed by the config PDA that holds the vault
+    let config_seeds: &[&[u8]] = &[b"dual_product_config", &[config.bump]];
+    if lst_to_user > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.vault_lst_reward_account.to_account_info(),
+                    to: ctx.accounts.user_lst_reward_account.to_account_info(),
+                    authority: config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            lst_to_user,
+        )?;
+    }
 
-            // Transfer LP rewards
-            if lst_to_user > 0 {
-                anchor_spl::token::transfer(
-                    CpiContext::new(
-                        ctx.accounts.token_program.to_account_info(),
-                        anchor_spl::token::Transfer {
-                            from: ctx.accounts.vault_lst_reward_account.to_account_info(),
-                            to: ctx.accounts.user_lst_reward_account.to_account_info(),
-                            authority: config.to_account_info(),
-                        },
-                    ),
-                    lst_to_user,
-                )?;
-            }
-
-            if usdc_to_user > 0 {
-                anchor_spl::token::transfer(
-                    CpiContext::new(
-                        ctx.accounts.token_program.to_account_info(),
-                        anchor_spl::token::Transfer {
-                            from: ctx.accounts.vault_usdc_reward_account.to_account_info(),
-                            to: ctx.accounts.user_usdc_reward_account.to_account_info(),
-                            authority: config.to_account_info(),
-                        },
-                    ),
-                    usdc_to_user,
-                )?;
-            }
-        },
-        RewardSource::Both => {
-            // Handle both reward sources directly instead of recursive calls
-            
-            // First handle LST rewards
-            let lst_rewards = calculate_lst_rewards(
-                user_position.lst_amount,
-                time_staked,
-                pool_state.lst_per_share,
-            )?;
-
-            let lst_fee = (lst_rewards as u128)
-                .checked_mul(config.platform_fee_bps as u128)
-                .ok_or(DualProductError::MathOverflow)?
-                .checked_div(10000)
-                .ok_or(DualProductError::MathOverflow)? as u64;
-
-            let lst_to_user = lst_rewards.checked_sub(lst_fee)
+    // Transfer USDC rewards
+    if usdc_to_user > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.vault_usdc_reward_account.to_account_info(),
+                    to: ctx.accounts.user_usdc_reward_account.to_account_info(),
+                    authority: config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            usdc_to_user,
+        )?;
+    }
+
+    // Transfer platform fees to the treasury
+    if lst_fee > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.vault_lst_reward_account.to_account_info(),
+                    to: ctx.accounts.treasury_lst_account.to_account_info(),
+                    authority: config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            lst_fee,
+        )?;
+    }
+    if usdc_fee > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.vault_usdc_reward_account.to_account_info(),
+                    to: ctx.accounts.treasury_usdc_account.to_account_info(),
+                    authority: config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            usdc_fee,
+        )?;
+    }
+
+    // Update last claim timestamp
+    user_position.last_reward_claim = next_claim_timestamp;
+
+    Ok(())
+}
+
+// Caps a claim at `max_claim_amount`, valued like `normalized_value` at the
+// larger of the two mint decimals. A partial claim scales both legs down and
+// advances the claim timestamp by the same fraction of the accrual window,
+// returning the legs to pay and the next claim timestamp
+fn cap_claim(
+    config: &DualProductConfig,
+    lst_amount: u64,
+    usdc_amount: u64,
+    last_reward_claim: i64,
+    current_time: i64,
+    max_claim_amount: Option<u64>,
+) -> Result<(u64, u64, i64)> {
+    let accrued_value = normalized_value(config, lst_amount, usdc_amount)?;
+
+    match max_claim_amount {
+        Some(max_claim_amount) if (max_claim_amount as u128) < accrued_value => {
+            let elapsed = current_time
+                .checked_sub(last_reward_claim)
                 .ok_or(DualProductError::MathOverflow)?;
+            let advance = scale_claim(elapsed as u64, max_claim_amount, accrued_value)? as i64;
 
-            // Transfer LST rewards
-            anchor_spl::token::transfer(
-                CpiContext::new(
-                    ctx.accounts.token_program.to_account_info(),
-                    anchor_spl::token::Transfer {
-                        from: ctx.accounts.vault_lst_reward_account.to_account_info(),
-                        to: ctx.accounts.user_lst_reward_account.to_account_info(),
-                        authority: config.to_account_info(),
-                    },
-                ),
-                lst_to_user,
-            )?;
-            
-            // Then handle LP rewards if eligible
-            if user_position.in_lp {
-                let lp_rewards = calculate_lp_rewards(
-                    user_position.lst_amount,
-                    user_position.usdc_amount,
-                    time_staked,
-                    pool_state,
-                )?;
-
-                let (lst_lp_rewards, usdc_lp_rewards) = lp_rewards;
-
-                let lst_fee = (lst_lp_rewards as u128)
-                    .checked_mul(config.platform_fee_bps as u128)
-                    .ok_or(DualProductError::MathOverflow)?
-                    .checked_div(10000)
-                    .ok_or(DualProductError::MathOverflow)? as u64;
-
-                let usdc_fee = (usdc_lp_rewards as u128)
-                    .checked_mul(config.platform_fee_bps as u128)
-                    .ok_or(DualProductError::MathOverflow)?
-                    .checked_div(10000)
-                    .ok_or(DualProductError::MathOverflow)? as u64;
-
-                let lst_to_user = lst_lp_rewards.checked_sub(lst_fee)
-                    .ok_or(DualProductError::MathOverflow)?;
-                let usdc_to_user = usdc_lp_rewards.checked_sub(usdc_fee)
-                    .ok_or(DualProductError::MathOverflow)?;
-
-                if lst_to_user > 0 {
-                    anchor_spl::token::transfer(
-                        CpiContext::new(
-                            ctx.accounts.token_program.to_account_info(),
-                            anchor_spl::token::Transfer {
-                                from: ctx.accounts.vault_lst_reward_account.to_account_info(),
-                                to: ctx.accounts.user_lst_reward_account.to_account_info(),
-                                authority: config.to_account_info(),
-                            },
-                        ),
-                        lst_to_user,
-                    )?;
-                }
-
-                if usdc_to_user > 0 {
-                    anchor_spl::token::transfer(
-                        CpiContext::new(
-                            ctx.accounts.token_program.to_account_info(),
-                            anchor_spl::token::Transfer {
-                                from: ctx.accounts.vault_usdc_reward_account.to_account_info(),
-                                to: ctx.accounts.user_usdc_reward_account.to_account_info(),
-                                authority: config.to_account_info(),
-                            },
-                        ),
-                        usdc_to_user,
-                    )?;
-                }
-            }
+            Ok((
+                scale_claim(lst_amount, max_claim_amount, accrued_value)?,
+                scale_claim(usdc_amount, max_claim_amount, accrued_value)?,
+                last_reward_claim + advance,
+            ))
         },
+        _ => Ok((lst_amount, usdc_amount, current_time)),
     }
+}
 
-    // Update last claim timestamp
-    user_position.last_reward_claim = current_time;
+// Whether a claim is worth less than the dust threshold, valued like
+// `normalized_value` at the larger of the two mint decimals
+fn is_dust(config: &DualProductConfig, lst_amount: u64, usdc_amount: u64) -> Result<bool> {
+    Ok(normalized_value(config, lst_amount, usdc_amount)? < config.dust_threshold as u128)
+}
+
+// Platform fee on the part of a reward leg actually paid out: the full
+// `gross - net` fee, scaled down with a capped claim
+fn claimed_fee(gross: u64, net: u64, paid: u64) -> Result<u64> {
+    let fee = gross.checked_sub(net).ok_or(DualProductError::MathOverflow)?;
+    if paid == net {
+        return Ok(fee);
+    }
+
+    let scaled = (fee as u128)
+        .checked_mul(paid as u128)
+        .ok_or(DualProductError::MathOverflow)?
+        .checked_div(net as u128)
+        .ok_or(DualProductError::MathOverflow)? as u64;
+
+    Ok(scaled)
+}
+
+// Scales `amount` by `max_claim_amount / accrued_value`, rounding down
+fn scale_claim(amount: u64, max_claim_amount: u64, accrued_value: u128) -> Result<u64> {
+    let scaled = (amount as u128)
+        .checked_mul(max_claim_amount as u128)
+        .ok_or(DualProductError::MathOverflow)?
+        .checked_div(accrued_value)
+        .ok_or(DualProductError::MathOverflow)? as u64;
+
+    Ok(scaled)
+}
+
+// View instruction: returns the net LST and USDC rewards a claim from
+// `reward_source` would pay out, without transferring or updating timestamps
+pub fn preview_dual_rewards(
+    ctx: Context<PreviewDualRewards>,
+    reward_source: RewardSource,
+) -> Result<()> {
+    let (lst_amount, usdc_amount) = pending_dual_rewards(
+        &ctx.accounts.config,
+        &ctx.accounts.user_position,
+        &ctx.accounts.pool_state,
+        reward_source,
+        Clock::get()?.unix_timestamp,
+        platform_fee_bps_for(
+            &ctx.accounts.config,
+            &ctx.accounts.vault_config,
+            &ctx.accounts.user_position.owner,
+        ),
+    )?;
+
+    let preview = DualRewardsPreview {
+        lst_amount,
+        usdc_amount,
+    };
+    set_return_data(&encode_view_response(PREVIEW_DUAL_REWARDS_VIEW_VERSION, &preview)?);
 
     Ok(())
 }
 
+// Net LST and USDC rewards a claim from `reward_source` would pay: zero
+// when none have accrued yet or they are worth less than the dust
+// threshold. LP claims require the position to be in the LP
+pub fn pending_dual_rewards(
+    config: &DualProductConfig,
+    user_position: &UserDualPosition,
+    pool_state: &PoolState,
+    reward_source: RewardSource,
+    current_time: i64,
+    platform_fee_bps: u16,
+) -> Result<(u64, u64)> {
+    let time_staked = current_time
+        .checked_sub(user_position.last_reward_claim)
+        .ok_or(DualProductError::MathOverflow)?;
+    if reward_source == RewardSource::LP {
+        require!(user_position.in_lp, DualProductError::PositionNotInLP);
+    }
+    if time_staked <= 0 {
+        return Ok((0, 0));
+    }
+
+    let (lst_amount, usdc_amount) = calculate_claimable_rewards(
+        config,
+        user_position,
+        pool_state,
+        reward_source,
+        time_staked,
+        platform_fee_bps,
+    )?;
+    if is_dust(config, lst_amount, usdc_amount)? {
+        return Ok((0, 0));
+    }
+
+    Ok((lst_amount, usdc_amount))
+}
+
+// Platform fee charged to `user`; accounts on the protocol's exemption list
+// pay none
+pub fn platform_fee_bps_for(config: &DualProductConfig, vault_config: &VaultConfig, user: &Pubkey) -> u16 {
+    if is_fee_exempt(vault_config, user) {
+        0
+    } else {
+        config.platform_fee_bps
+    }
+}
+
+// Helper function to calculate the LST and USDC rewards owed to the user
+// after platform fees for the given reward source.
+//
+// Fee semantics: `platform_fee_bps` is charged once per reward component
+// (LST staking rewards, LP rewards in LST, LP rewards in USDC), each rounded
+// down on its own. `RewardSource::Both` is the sum of the LST and LP claims,
+// so it pays exactly the same fees as claiming each source separately rather
+// than a single fee on the combined total.
+fn calculate_claimable_rewards(
+    config: &DualProductConfig,
+    user_position: &UserDualPosition,
+    pool_state: &PoolState,
+    reward_source: RewardSource,
+    time_staked: i64,
+    platform_fee_bps: u16,
+) -> Result<(u64, u64)> {
+    let mut lst_to_user: u64 = 0;
+    let mut usdc_to_user: u64 = 0;
+
+    if reward_source == RewardSource::LST || reward_source == RewardSource::Both {
+        // Calculate LST rewards based on staking duration
+        let lst_rewards = calculate_lst_rewards(
+            user_position.lst_amount,
+            time_staked,
+            pool_state.lst_per_share,
+        )?;
+
+        lst_to_user = deduct_platform_fee(lst_rewards, platform_fee_bps)?;
+    }
+
+    if reward_source == RewardSource::LP
+        || (reward_source == RewardSource::Both && user_position.in_lp)
+    {
+        // Calculate LP rewards, split into LST and USDC components
+        let (lst_lp_rewards, usdc_lp_rewards) = calculate_lp_rewards(
+            config,
+            user_position.lst_amount,
+            user_position.usdc_amount,
+            time_staked,
+            pool_state,
+        )?;
+
+        let lst_lp_to_user = deduct_platform_fee(lst_lp_rewards, platform_fee_bps)?;
+        let usdc_lp_to_user = deduct_platform_fee(usdc_lp_rewards, platform_fee_bps)?;
+
+        lst_to_user = lst_to_user.checked_add(lst_lp_to_user)
+            .ok_or(DualProductError::MathOverflow)?;
+        usdc_to_user = usdc_to_user.checked_add(usdc_lp_to_user)
+            .ok_or(DualProductError::MathOverflow)?;
+    }
+
+    Ok((lst_to_user, usdc_to_user))
+}
+
+// Helper function to apply the platform fee to a single reward component
+fn deduct_platform_fee(rewards: u64, platform_fee_bps: u16) -> Result<u64> {
+    let fee = apply_bps(rewards, platform_fee_bps)
+        .ok_or(DualProductError::MathOverflow)?;
+
+    let to_user = rewards.checked_sub(fee)
+        .ok_or(DualProductError::MathOverflow)?;
+
+    Ok(to_user)
+}
+
 // Helper function to calculate LST staking rewards
 fn calculate_lst_rewards(
     lst_amount: u64,
     time_staked: i64,
-    lst_per_share: u64,
+    lst_per_share: u128,
 ) -> Result<u64> {
     // Simple reward calculation based on amount staked and time
     // In production, this would use more complex tokenomics
     let base_reward = (lst_amount as u128)
         .checked_mul(time_staked as u128)
         .ok_or(DualProductError::MathOverflow)?
-        .checked_mul(lst_per_share as u128)
+        .checked_mul(lst_per_share)
         .ok_or(DualProductError::MathOverflow)?
-        .checked_div(1_000_000_000)
+        .checked_div(REWARD_INDEX_SCALE)
         .ok_or(DualProductError::MathOverflow)? as u64;
 
     Ok(base_reward)
 }
 
+// Helper function to scale a token amount up to `common_decimals`
+pub fn normalize_amount(amount: u64, decimals: u8, common_decimals: u8) -> Result<u128> {
+    let scale = 10u128
+        .checked_pow(common_decimals.saturating_sub(decimals) as u32)
+        .ok_or(DualProductError::MathOverflow)?;
+
+    Ok((amount as u128)
+        .checked_mul(scale)
+        .ok_or(DualProductError::MathOverflow)?)
+}
+
+// Helper function to sum both legs at the larger of the two mint decimals
+pub fn normalized_value(config: &DualProductConfig, lst_amount: u64, usdc_amount: u64) -> Result<u128> {
+    let common_decimals = config.lst_decimals.max(config.usdc_decimals);
+
+    Ok(normalize_amount(lst_amount, config.lst_decimals, common_decimals)?
+        .checked_add(normalize_amount(usdc_amount, config.usdc_decimals, common_decimals)?)
+        .ok_or(DualProductError::MathOverflow)?)
+}
+
 // Helper function to calculate LP rewards
 fn calculate_lp_rewards(
+    config: &DualProductConfig,
     lst_amount: u64,
     usdc_amount: u64,
     time_staked: i64,
     pool_state: &PoolState,
 ) -> Result<(u64, u64)> {
-    // Calculate share of pool
-    let total_value = (lst_amount as u128)
-        .checked_add(usdc_amount as u128)
-        .ok_or(DualProductError::MathOverflow)?;
+    // Calculate share of pool with both legs at a common decimal scale so
+    // neither dominates just by having more decimals
+    let total_value = normalized_value(config, lst_amount, usdc_amount)?;
     
-    let pool_total = (pool_state.total_lst as u128)
-        .checked_add(pool_state.total_usdc as u128)
-        .ok_or(DualProductError::MathOverflow)?;
+    let pool_total = normalized_value(config, pool_state.total_lst, pool_state.total_usdc)?;
     
     let share_ratio = total_value
         .checked_mul(1_000_000_000)
@@ -298,18 +520,283 @@ fn calculate_lp_rewards(
     let lst_reward = (share_ratio as u128)
         .checked_mul(time_staked as u128)
         .ok_or(DualProductError::MathOverflow)?
-        .checked_mul(pool_state.lst_per_share as u128)
+        .checked_mul(pool_state.lst_per_share)
         .ok_or(DualProductError::MathOverflow)?
-        .checked_div(1_000_000_000)
+        .checked_div(REWARD_INDEX_SCALE)
         .ok_or(DualProductError::MathOverflow)? as u64;
 
     let usdc_reward = (share_ratio as u128)
         .checked_mul(time_staked as u128)
         .ok_or(DualProductError::MathOverflow)?
-        .checked_mul(pool_state.usdc_per_share as u128)
+        .checked_mul(pool_state.usdc_per_share)
         .ok_or(DualProductError::MathOverflow)?
-        .checked_div(1_000_000_000)
+        .checked_div(REWARD_INDEX_SCALE)
         .ok_or(DualProductError::MathOverflow)? as u64;
 
     Ok((lst_reward, usdc_reward))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{FeeModel, FeeTier, MAX_FEE_TIERS};
+    use vault_math::per_second_rate;
+
+    const LST: u64 = 1_000_000_000;
+    const USDC: u64 = 1_000_000;
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn config(lst_decimals: u8, usdc_decimals: u8) -> DualProductConfig {
+        DualProductConfig {
+            authority: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            platform_fee_bps: 50,
+            min_deposit_amount: 0,
+            lst_ratio: 5000,
+            usdc_ratio: 5000,
+            fee_model: FeeModel::FlatWithdrawal { fee_bps: 50 },
+            fee_tiers: [FeeTier::default(); MAX_FEE_TIERS],
+            fee_tier_count: 0,
+            loyalty_min_tenure_days: 0,
+            loyalty_discount_bps: 0,
+            min_lp_position_value: 0,
+            dust_threshold: 0,
+            lst_reward_mint: Pubkey::new_unique(),
+            usdc_reward_mint: Pubkey::new_unique(),
+            lst_decimals,
+            usdc_decimals,
+            paused: false,
+            pause_flags: 0,
+            bump: 255,
+        }
+    }
+
+    fn pool_state(total_lst: u64, total_usdc: u64) -> PoolState {
+        PoolState {
+            total_lst,
+            total_usdc,
+            total_shares: 0,
+            lst_per_share: per_second_rate(700, REWARD_INDEX_SCALE).unwrap(),
+            usdc_per_share: per_second_rate(500, REWARD_INDEX_SCALE).unwrap(),
+            last_update: 0,
+            bump: 255,
+        }
+    }
+
+    fn position(lst_amount: u64, usdc_amount: u64) -> UserDualPosition {
+        UserDualPosition {
+            owner: Pubkey::new_unique(),
+            lst_amount,
+            usdc_amount,
+            lst_principal: lst_amount,
+            usdc_principal: usdc_amount,
+            in_lp: true,
+            deposit_timestamp: 0,
+            last_reward_claim: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn normalize_amount_scales_to_common_decimals() {
+        // 1 USDC (6 decimals) and 1 LST (9 decimals) weigh the same
+        assert_eq!(normalize_amount(1_000_000, 6, 9).unwrap(), 1_000_000_000);
+        assert_eq!(normalize_amount(1_000_000_000, 9, 9).unwrap(), 1_000_000_000);
+    }
+
+    #[test]
+    fn normalize_amount_holds_u64_max_at_large_scales() {
+        let scaled = normalize_amount(u64::MAX, 0, 18).unwrap();
+        assert_eq!(scaled, u64::MAX as u128 * 10u128.pow(18));
+    }
+
+    #[test]
+    fn hourly_lst_claims_sum_to_the_annual_rate() {
+        // 10 LST at 7% APY claimed every hour for a year
+        let lst_per_share = per_second_rate(700, REWARD_INDEX_SCALE).unwrap();
+
+        let claimed: u64 = (0..365 * 24)
+            .map(|_| calculate_lst_rewards(10 * LST, 60 * 60, lst_per_share).unwrap())
+            .sum();
+
+        // Each claim rounds down by less than one unit
+        let annual = 7 * LST / 10;
+        assert!(claimed <= annual && annual - claimed <= 365 * 24);
+    }
+
+    #[test]
+    fn both_sources_pay_the_sum_of_each_source() {
+        let config = config(9, 6);
+        let pool_state = pool_state(100 * LST, 10_000 * USDC);
+        let user_position = position(10 * LST, 1_000 * USDC);
+        let claim = |reward_source| {
+            calculate_claimable_rewards(&config, &user_position, &pool_state, reward_source, 30 * DAY, 50)
+                .unwrap()
+        };
+
+        let (lst_only, _) = claim(RewardSource::LST);
+        let (lst_from_lp, usdc_from_lp) = claim(RewardSource::LP);
+        assert_eq!(claim(RewardSource::Both), (lst_only + lst_from_lp, usdc_from_lp));
+    }
+
+    #[test]
+    fn platform_fee_is_charged_on_each_component() {
+        let config = config(9, 6);
+        let pool_state = pool_state(100 * LST, 10_000 * USDC);
+        let user_position = position(10 * LST, 1_000 * USDC);
+        let claim = |reward_source, platform_fee_bps| {
+            calculate_claimable_rewards(
+                &config,
+                &user_position,
+                &pool_state,
+                reward_source,
+                30 * DAY,
+                platform_fee_bps,
+            )
+            .unwrap()
+        };
+
+        let (gross_lst, _) = claim(RewardSource::LST, 0);
+        let (gross_lst_from_lp, gross_usdc_from_lp) = claim(RewardSource::LP, 0);
+        let (net_lst, net_usdc) = claim(RewardSource::Both, 50);
+
+        assert_eq!(
+            net_lst,
+            deduct_platform_fee(gross_lst, 50).unwrap() + deduct_platform_fee(gross_lst_from_lp, 50).unwrap()
+        );
+        assert_eq!(net_usdc, deduct_platform_fee(gross_usdc_from_lp, 50).unwrap());
+    }
+
+    #[test]
+    fn lp_rewards_do_not_depend_on_lst_decimals() {
+        let native = calculate_lp_rewards(
+            &config(9, 6),
+            10 * LST,
+            1_000 * USDC,
+            30 * DAY,
+            &pool_state(100 * LST, 10_000 * USDC),
+        )
+        .unwrap();
+
+        // The same deposit with the LST leg held at USDC's 6 decimals
+        let rescaled = calculate_lp_rewards(
+            &config(6, 6),
+            10 * USDC,
+            1_000 * USDC,
+            30 * DAY,
+            &pool_state(100 * USDC, 10_000 * USDC),
+        )
+        .unwrap();
+
+        assert_eq!(native, rescaled);
+    }
+
+    #[test]
+    fn equal_whole_unit_legs_earn_equal_lp_rewards() {
+        let config = config(9, 6);
+        let pool_state = pool_state(100 * LST, 100 * USDC);
+
+        let lst_only = calculate_lp_rewards(&config, 100 * LST, 0, 30 * DAY, &pool_state).unwrap();
+        let usdc_only = calculate_lp_rewards(&config, 0, 100 * USDC, 30 * DAY, &pool_state).unwrap();
+
+        assert_eq!(lst_only, usdc_only);
+    }
+
+    #[test]
+    fn scale_claim_rounds_down() {
+        assert_eq!(scale_claim(100, 1, 3).unwrap(), 33);
+        assert_eq!(scale_claim(100, 3, 3).unwrap(), 100);
+        assert!(scale_claim(100, 1, 0).is_err());
+    }
+
+    #[test]
+    fn half_claim_leaves_the_remainder_claimable() {
+        let config = config(9, 6);
+        let pool_state = pool_state(100 * LST, 10_000 * USDC);
+        let user_position = position(10 * LST, 1_000 * USDC);
+        let claim = |time_staked| {
+            calculate_claimable_rewards(&config, &user_position, &pool_state, RewardSource::Both, time_staked, 0)
+                .unwrap()
+        };
+
+        let window = 30 * DAY;
+        let (lst, usdc) = claim(window);
+        let accrued_value = normalized_value(&config, lst, usdc).unwrap();
+        let half = (accrued_value / 2) as u64;
+
+        let (claimed_lst, claimed_usdc, next_claim) =
+            cap_claim(&config, lst, usdc, 0, window, Some(half)).unwrap();
+        let claimed = normalized_value(&config, claimed_lst, claimed_usdc).unwrap();
+        assert!(claimed <= half as u128 && half as u128 - claimed <= 1_000);
+        assert!((next_claim - window / 2).abs() <= 1);
+
+        // The rest of the window pays out what the first claim left behind
+        let (remaining_lst, remaining_usdc) = claim(window - next_claim);
+        assert!(claimed_lst + remaining_lst <= lst && lst - (claimed_lst + remaining_lst) <= 2);
+        assert!(claimed_usdc + remaining_usdc <= usdc && usdc - (claimed_usdc + remaining_usdc) <= 2);
+    }
+
+    #[test]
+    fn claim_cap_values_legs_of_different_decimals_alike() {
+        let config = config(9, 6);
+
+        // 1 LST and 1 USDC are worth 2e9 at 9 decimals, so a 1e9 cap halves both
+        let (lst, usdc, next_claim) = cap_claim(&config, LST, USDC, 0, DAY, Some(LST)).unwrap();
+        assert_eq!((lst, usdc, next_claim), (LST / 2, USDC / 2, DAY / 2));
+
+        // A raw sum would have let a cap of 1 LST + 1 micro-USDC claim everything
+        let (lst, usdc, _) = cap_claim(&config, LST, USDC, 0, DAY, Some(LST + USDC)).unwrap();
+        assert!(lst < LST && usdc < USDC);
+
+        let uncapped = cap_claim(&config, LST, USDC, 0, DAY, Some(2 * LST)).unwrap();
+        assert_eq!(uncapped, (LST, USDC, DAY));
+    }
+
+    #[test]
+    fn dust_is_valued_across_both_legs() {
+        let mut config = config(9, 6);
+        config.dust_threshold = 1_000_000;
+
+        // 999 micro-USDC is worth 999_000 at 9 decimals, below the threshold
+        assert!(is_dust(&config, 0, 999).unwrap());
+        // 1000 micro-USDC is not, though it is only a thousand base units
+        assert!(!is_dust(&config, 0, 1_000).unwrap());
+        assert!(!is_dust(&config, 500_000, 500).unwrap());
+        assert!(is_dust(&config, 999_999, 0).unwrap());
+    }
+
+    #[test]
+    fn claimed_fee_follows_a_capped_claim() {
+        assert_eq!(claimed_fee(1_000, 995, 995).unwrap(), 5);
+        assert_eq!(claimed_fee(2_000, 1_990, 995).unwrap(), 5);
+        assert_eq!(claimed_fee(0, 0, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn pending_rewards_reject_lp_claims_outside_the_lp() {
+        let config = config(9, 6);
+        let pool_state = pool_state(100 * LST, 10_000 * USDC);
+        let mut user_position = position(10 * LST, 1_000 * USDC);
+        user_position.in_lp = false;
+
+        let result = pending_dual_rewards(&config, &user_position, &pool_state, RewardSource::LP, DAY, 50);
+        assert_eq!(result.unwrap_err(), DualProductError::PositionNotInLP.into());
+        assert!(pending_dual_rewards(&config, &user_position, &pool_state, RewardSource::LST, DAY, 50).is_ok());
+    }
+
+    #[test]
+    fn pending_rewards_defer_dust() {
+        let mut config = config(9, 6);
+        let pool_state = pool_state(100 * LST, 10_000 * USDC);
+        let user_position = position(10 * LST, 1_000 * USDC);
+        let pending = |config: &DualProductConfig| {
+            pending_dual_rewards(config, &user_position, &pool_state, RewardSource::Both, 60, 50).unwrap()
+        };
+
+        let (lst, usdc) = pending(&config);
+        assert!(lst > 0);
+
+        config.dust_threshold = normalized_value(&config, lst, usdc).unwrap() as u64 + 1;
+        assert_eq!(pending(&config), (0, 0));
+    }
+}
@@ -1,7 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
-use crate::state::{DualProductConfig, UserDualPosition, PoolState};
+use crate::state::{DualProductConfig, UserDualPosition, PoolState, UserVolumeTracker, UserLoyaltyProfile, FeeModel, RewardSource, WITHDRAWAL_BIT, VOLUME_WINDOW_SLOTS};
 use crate::errors::DualProductError;
+use vault_sol::state::{GlobalPause, VaultConfig};
+use vault_sol::utils::is_fee_exempt;
+use vault_math::apply_bps;
+use crate::instructions::deposits::refresh_loyalty_profile;
+use crate::instructions::rewards::{normalized_value, pending_dual_rewards, platform_fee_bps_for};
 
 #[derive(Accounts)]
 pub struct WithdrawDual<'info> {
@@ -9,9 +14,26 @@ pub struct WithdrawDual<'info> {
         seeds = [b"dual_product_config"],
         bump = config.bump,
         constraint = !config.paused @ DualProductError::ProductPaused,
+        constraint = config.pause_flags & (1 << WITHDRAWAL_BIT) == 0 @ DualProductError::OperationPaused,
     )]
     pub config: Account<'info, DualProductConfig>,
 
+    // Protocol-wide fee exemption list
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ DualProductError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"user_dual_position", user.key().as_ref()],
@@ -28,25 +50,72 @@ pub struct WithdrawDual<'info> {
     )]
     pub pool_state: Account<'info, PoolState>,
 
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + std::mem::size_of::<UserVolumeTracker>(),
+        seeds = [b"user_volume_tracker", user.key().as_ref()],
+        bump
+    )]
+    pub volume_tracker: Account<'info, UserVolumeTracker>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + std::mem::size_of::<UserLoyaltyProfile>(),
+        seeds = [b"loyalty", user.key().as_ref()],
+        bump
+    )]
+    pub loyalty_profile: Account<'info, UserLoyaltyProfile>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
-    // LST Token accounts
+    // LST Token accounts; the vault side is held by the config PDA
+    #[account(constraint = lst_mint.key() == config.lst_reward_mint @ DualProductError::InvalidTokenMint)]
     pub lst_mint: Account<'info, Mint>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_lst_account.owner == user.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = user_lst_account.mint == lst_mint.key() @ DualProductError::InvalidTokenMint,
+    )]
     pub user_lst_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = vault_lst_account.owner == config.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = vault_lst_account.mint == lst_mint.key() @ DualProductError::InvalidTokenMint,
+    )]
     pub vault_lst_account: Account<'info, TokenAccount>,
 
-    // USDC Token accounts
+    // USDC Token accounts; the vault side is held by the config PDA
+    #[account(constraint = usdc_mint.key() == config.usdc_reward_mint @ DualProductError::InvalidTokenMint)]
     pub usdc_mint: Account<'info, Mint>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_usdc_account.owner == user.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = user_usdc_account.mint == usdc_mint.key() @ DualProductError::InvalidTokenMint,
+    )]
     pub user_usdc_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = vault_usdc_account.owner == config.key() @ DualProductError::InvalidTokenAccountOwner,
+        constraint = vault_usdc_account.mint == usdc_mint.key() @ DualProductError::InvalidTokenMint,
+    )]
     pub vault_usdc_account: Account<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub treasury: SystemAccount<'info>,
+    // Withdrawal fees go to the configured treasury
+    #[account(
+        mut,
+        constraint = treasury_lst_account.owner == config.treasury @ DualProductError::InvalidTokenAccountOwner,
+        constraint = treasury_lst_account.mint == lst_mint.key() @ DualProductError::InvalidTokenMint,
+    )]
+    pub treasury_lst_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_usdc_account.owner == config.treasury @ DualProductError::InvalidTokenAccountOwner,
+        constraint = treasury_usdc_account.mint == usdc_mint.key() @ DualProductError::InvalidTokenMint,
+    )]
+    pub treasury_usdc_account: Account<'info, TokenAccount>,
 
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -68,50 +137,110 @@ pub fn withdraw_dual(
         DualProductError::InsufficientBalance
     );
 
+    // Decay the rolling volume before selecting the fee tier
+    let volume_tracker = &mut ctx.accounts.volume_tracker;
+    if volume_tracker.user == Pubkey::default() {
+        volume_tracker.user = ctx.accounts.user.key();
+        volume_tracker.bump = *ctx.bumps.get("volume_tracker").unwrap();
+    }
+    let current_slot = Clock::get()?.slot;
+    let rolling_volume = decayed_volume(volume_tracker, current_slot)?;
+
+    let loyalty_profile = &mut ctx.accounts.loyalty_profile;
+    let loyalty_bump = *ctx.bumps.get("loyalty_profile").unwrap();
+    let current_time = Clock::get()?.unix_timestamp;
+    refresh_loyalty_profile(loyalty_profile, ctx.accounts.user.key(), loyalty_bump, current_time);
+    let loyalty_discount_bps = if loyalty_profile.consecutive_staking_days >= config.loyalty_min_tenure_days {
+        config.loyalty_discount_bps
+    } else {
+        0
+    };
+
+    let fee_exempt = is_fee_exempt(&ctx.accounts.vault_config, ctx.accounts.user.key);
+    let fee_model = effective_fee_model(config, rolling_volume, loyalty_discount_bps, fee_exempt);
+
+    // Principal attributable to this withdrawal, pro rata to each leg
+    let lst_cost_basis = withdrawal_cost_basis(lst_amount, user_position.lst_principal, user_position.lst_amount)?;
+    let usdc_cost_basis = withdrawal_cost_basis(usdc_amount, user_position.usdc_principal, user_position.usdc_amount)?;
+
     // Calculate fees
-    let lst_fee = (lst_amount as u128)
-        .checked_mul(config.platform_fee_bps as u128)
-        .ok_or(DualProductError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(DualProductError::MathOverflow)? as u64;
-    
-    let usdc_fee = (usdc_amount as u128)
-        .checked_mul(config.platform_fee_bps as u128)
-        .ok_or(DualProductError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(DualProductError::MathOverflow)? as u64;
+    let lst_fee = calculate_withdrawal_fee(fee_model, lst_amount, lst_cost_basis)?;
+    let usdc_fee = calculate_withdrawal_fee(fee_model, usdc_amount, usdc_cost_basis)?;
 
     let lst_withdraw = lst_amount.checked_sub(lst_fee)
         .ok_or(DualProductError::MathOverflow)?;
     let usdc_withdraw = usdc_amount.checked_sub(usdc_fee)
         .ok_or(DualProductError::MathOverflow)?;
 
-    // Transfer LST tokens to user
+    // Transfer LST tokens to user, signed by the config PDA that holds the vault
+    let config_seeds: &[&[u8]] = &[b"dual_product_config", &[config.bump]];
     anchor_spl::token::transfer(
-        CpiContext::new(
+        CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             Transfer {
                 from: ctx.accounts.vault_lst_account.to_account_info(),
                 to: ctx.accounts.user_lst_account.to_account_info(),
                 authority: config.to_account_info(),
             },
+            &[config_seeds],
         ),
         lst_withdraw,
     )?;
 
     // Transfer USDC tokens to user
     anchor_spl::token::transfer(
-        CpiContext::new(
+        CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             Transfer {
                 from: ctx.accounts.vault_usdc_account.to_account_info(),
                 to: ctx.accounts.user_usdc_account.to_account_info(),
                 authority: config.to_account_info(),
             },
+            &[config_seeds],
         ),
         usdc_withdraw,
     )?;
 
+    // Transfer fees to the treasury
+    if lst_fee > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_lst_account.to_account_info(),
+                    to: ctx.accounts.treasury_lst_account.to_account_info(),
+                    authority: config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            lst_fee,
+        )?;
+    }
+    if usdc_fee > 0 {
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_usdc_account.to_account_info(),
+                    to: ctx.accounts.treasury_usdc_account.to_account_info(),
+                    authority: config.to_account_info(),
+                },
+                &[config_seeds],
+            ),
+            usdc_fee,
+        )?;
+    }
+
+    // Rewards accrue on the balance held before this withdrawal
+    let pending_rewards = pending_dual_rewards(
+        config,
+        user_position,
+        pool_state,
+        RewardSource::Both,
+        current_time,
+        platform_fee_bps_for(config, &ctx.accounts.vault_config, ctx.accounts.user.key),
+    )?;
+
     // Update user position
     user_position.lst_amount = user_position.lst_amount
         .checked_sub(lst_amount)
@@ -119,6 +248,8 @@ pub fn withdraw_dual(
     user_position.usdc_amount = user_position.usdc_amount
         .checked_sub(usdc_amount)
         .ok_or(DualProductError::MathOverflow)?;
+    user_position.lst_principal = user_position.lst_principal.saturating_sub(lst_cost_basis);
+    user_position.usdc_principal = user_position.usdc_principal.saturating_sub(usdc_cost_basis);
 
     // Update pool state
     pool_state.total_lst = pool_state.total_lst
@@ -129,5 +260,233 @@ pub fn withdraw_dual(
         .ok_or(DualProductError::MathOverflow)?;
     pool_state.last_update = Clock::get()?.unix_timestamp;
 
+    // Record this withdrawal's volume
+    volume_tracker.rolling_30d_volume = add_volume(config, rolling_volume, lst_amount, usdc_amount)?;
+    volume_tracker.last_update_slot = current_slot;
+
+    // Fully exiting breaks the staking streak
+    if user_position.lst_amount == 0 && user_position.usdc_amount == 0 {
+        loyalty_profile.first_stake_timestamp = 0;
+        loyalty_profile.consecutive_staking_days = 0;
+    }
+
+    // Refund the position rent once nothing is left to claim; claiming in
+    // the same transaction before withdrawing settles pending rewards. The
+    // volume tracker and loyalty profile go with it, since a full exit has
+    // already reset the streak
+    if position_closable(user_position, pending_rewards) {
+        user_position.close(ctx.accounts.user.to_account_info())?;
+        volume_tracker.close(ctx.accounts.user.to_account_info())?;
+        loyalty_profile.close(ctx.accounts.user.to_account_info())?;
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
+
+// A position can be closed once it is empty, out of the LP and owed no
+// rewards
+fn position_closable(user_position: &UserDualPosition, pending_rewards: (u64, u64)) -> bool {
+    user_position.lst_amount == 0
+        && user_position.usdc_amount == 0
+        && !user_position.in_lp
+        && pending_rewards == (0, 0)
+}
+
+// Rolling volume decayed linearly over the 30-day window since the last update
+pub fn decayed_volume(tracker: &UserVolumeTracker, current_slot: u64) -> Result<u64> {
+    let elapsed = current_slot.saturating_sub(tracker.last_update_slot);
+    if elapsed >= VOLUME_WINDOW_SLOTS {
+        return Ok(0);
+    }
+
+    let volume = (tracker.rolling_30d_volume as u128)
+        .checked_mul((VOLUME_WINDOW_SLOTS - elapsed) as u128)
+        .ok_or(DualProductError::MathOverflow)?
+        .checked_div(VOLUME_WINDOW_SLOTS as u128)
+        .ok_or(DualProductError::MathOverflow)? as u64;
+
+    Ok(volume)
+}
+
+// Rolling volume plus a trade of both legs, valued like `normalized_value`
+// at the larger of the two mint decimals
+pub fn add_volume(
+    config: &DualProductConfig,
+    rolling_volume: u64,
+    lst_amount: u64,
+    usdc_amount: u64,
+) -> Result<u64> {
+    let trade_value = normalized_value(config, lst_amount, usdc_amount)?;
+
+    Ok(rolling_volume.saturating_add(u64::try_from(trade_value).unwrap_or(u64::MAX)))
+}
+
+// Fee model with its rate replaced by the first tier the user's volume
+// reaches, less any loyalty discount; zero for fee-exempt accounts
+fn effective_fee_model(
+    config: &DualProductConfig,
+    rolling_volume: u64,
+    loyalty_discount_bps: u16,
+    fee_exempt: bool,
+) -> FeeModel {
+    let tier = config.fee_tiers[..config.fee_tier_count as usize]
+        .iter()
+        .find(|tier| tier.volume_threshold <= rolling_volume);
+
+    let base_fee_bps = match (tier, config.fee_model) {
+        (Some(tier), _) => tier.fee_bps,
+        (None, FeeModel::FlatWithdrawal { fee_bps } | FeeModel::ProfitOnly { fee_bps }) => fee_bps,
+    };
+    let fee_bps = if fee_exempt {
+        0
+    } else {
+        base_fee_bps - base_fee_bps.min(loyalty_discount_bps)
+    };
+
+    match config.fee_model {
+        FeeModel::FlatWithdrawal { .. } => FeeModel::FlatWithdrawal { fee_bps },
+        FeeModel::ProfitOnly { .. } => FeeModel::ProfitOnly { fee_bps },
+    }
+}
+
+// Principal attributable to withdrawing `amount` from a leg holding
+// `balance`, pro rata to the principal left in it
+fn withdrawal_cost_basis(amount: u64, principal: u64, balance: u64) -> Result<u64> {
+    if balance == 0 {
+        return Ok(0);
+    }
+
+    let cost_basis = (amount as u128)
+        .checked_mul(principal as u128)
+        .ok_or(DualProductError::MathOverflow)?
+        .checked_div(balance as u128)
+        .ok_or(DualProductError::MathOverflow)? as u64;
+
+    Ok(cost_basis)
+}
+
+// Helper function to calculate the withdrawal fee under the configured model
+fn calculate_withdrawal_fee(
+    fee_model: FeeModel,
+    amount: u64,
+    cost_basis: u64,
+) -> Result<u64> {
+    let (fee_base, fee_bps) = match fee_model {
+        FeeModel::FlatWithdrawal { fee_bps } => (amount, fee_bps),
+        FeeModel::ProfitOnly { fee_bps } => (amount.saturating_sub(cost_basis), fee_bps),
+    };
+
+    let fee = apply_bps(fee_base, fee_bps)
+        .ok_or(DualProductError::MathOverflow)?;
+
+    Ok(fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{FeeTier, MAX_FEE_TIERS};
+
+    fn position(lst_amount: u64, usdc_amount: u64, in_lp: bool) -> UserDualPosition {
+        UserDualPosition {
+            owner: Pubkey::new_unique(),
+            lst_amount,
+            usdc_amount,
+            lst_principal: lst_amount,
+            usdc_principal: usdc_amount,
+            in_lp,
+            deposit_timestamp: 0,
+            last_reward_claim: 0,
+            bump: 255,
+        }
+    }
+
+    fn config_with_flat_fee(fee_bps: u16) -> DualProductConfig {
+        DualProductConfig {
+            authority: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            platform_fee_bps: 50,
+            min_deposit_amount: 0,
+            lst_ratio: 5000,
+            usdc_ratio: 5000,
+            fee_model: FeeModel::FlatWithdrawal { fee_bps },
+            fee_tiers: [FeeTier::default(); MAX_FEE_TIERS],
+            fee_tier_count: 0,
+            loyalty_min_tenure_days: 0,
+            loyalty_discount_bps: 0,
+            min_lp_position_value: 0,
+            dust_threshold: 0,
+            lst_reward_mint: Pubkey::new_unique(),
+            usdc_reward_mint: Pubkey::new_unique(),
+            lst_decimals: 9,
+            usdc_decimals: 6,
+            paused: false,
+            pause_flags: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn fee_exempt_accounts_pay_no_withdrawal_fee() {
+        let config = config_with_flat_fee(100);
+
+        let normal = effective_fee_model(&config, 0, 0, false);
+        assert!(normal == FeeModel::FlatWithdrawal { fee_bps: 100 });
+        assert_eq!(calculate_withdrawal_fee(normal, 1_000_000, 1_000_000).unwrap(), 10_000);
+
+        let exempt = effective_fee_model(&config, 0, 0, true);
+        assert!(exempt == FeeModel::FlatWithdrawal { fee_bps: 0 });
+        assert_eq!(calculate_withdrawal_fee(exempt, 1_000_000, 1_000_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn fully_withdrawn_settled_position_is_closable() {
+        assert!(position_closable(&position(0, 0, false), (0, 0)));
+    }
+
+    #[test]
+    fn position_with_pending_rewards_is_not_closable() {
+        assert!(!position_closable(&position(0, 0, false), (1, 0)));
+        assert!(!position_closable(&position(0, 0, false), (0, 1)));
+    }
+
+    #[test]
+    fn position_in_lp_or_with_balance_is_not_closable() {
+        assert!(!position_closable(&position(0, 0, true), (0, 0)));
+        assert!(!position_closable(&position(1, 0, false), (0, 0)));
+        assert!(!position_closable(&position(0, 1, false), (0, 0)));
+    }
+
+    #[test]
+    fn profit_only_charges_the_gain_above_principal() {
+        let mut config = config_with_flat_fee(0);
+        config.fee_model = FeeModel::ProfitOnly { fee_bps: 1000 };
+        let fee_model = effective_fee_model(&config, 0, 0, false);
+
+        // 1_200 held against 1_000 principal: half of it carries 500 basis
+        let cost_basis = withdrawal_cost_basis(600, 1_000, 1_200).unwrap();
+        assert_eq!(cost_basis, 500);
+        assert_eq!(calculate_withdrawal_fee(fee_model, 600, cost_basis).unwrap(), 10);
+    }
+
+    #[test]
+    fn profit_only_charges_nothing_on_principal() {
+        let mut config = config_with_flat_fee(0);
+        config.fee_model = FeeModel::ProfitOnly { fee_bps: 1000 };
+        let fee_model = effective_fee_model(&config, 0, 0, false);
+
+        let cost_basis = withdrawal_cost_basis(600, 1_000, 1_000).unwrap();
+        assert_eq!(cost_basis, 600);
+        assert_eq!(calculate_withdrawal_fee(fee_model, 600, cost_basis).unwrap(), 0);
+    }
+
+    #[test]
+    fn volume_values_legs_of_different_decimals_alike() {
+        let config = config_with_flat_fee(0);
+
+        // One whole LST and one whole USDC count the same at 9 decimals
+        assert_eq!(add_volume(&config, 0, 1_000_000_000, 1_000_000).unwrap(), 2_000_000_000);
+        assert_eq!(add_volume(&config, 5, 0, 1).unwrap(), 1_005);
+        assert_eq!(add_volume(&config, u64::MAX - 1, 1, 0).unwrap(), u64::MAX);
+    }
+}
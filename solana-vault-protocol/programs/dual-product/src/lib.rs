@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 
 pub mod errors;
+pub mod events;
 pub mod state;
 pub mod instructions;
+pub mod view;
 
 use instructions::*;
 use state::*;
@@ -23,6 +25,14 @@ pub mod dual_product {
         instructions::admin::initialize_product(ctx, platform_fee_bps, min_deposit, lst_ratio, usdc_ratio)
     }
     
+    pub fn initialize_dual_product(
+        ctx: Context<InitializeDualProduct>,
+        platform_fee_bps: u16,
+        min_dual_amount: u64,
+    ) -> Result<()> {
+        instructions::admin::initialize_dual_product(ctx, platform_fee_bps, min_dual_amount)
+    }
+
     pub fn create_dual_position(
         ctx: Context<CreateDualPosition>,
         wsol_amount: u64,
@@ -39,6 +49,14 @@ pub mod dual_product {
         instructions::deposits::add_to_position(ctx, wsol_amount, usdc_amount)
     }
 
+    pub fn deposit_dual(
+        ctx: Context<DepositDual>,
+        lst_amount: u64,
+        usdc_amount: u64,
+    ) -> Result<()> {
+        instructions::deposits::deposit_dual(ctx, lst_amount, usdc_amount)
+    }
+
     pub fn withdraw_dual(
         ctx: Context<WithdrawDual>,
         lst_amount: u64,
@@ -58,8 +76,16 @@ pub mod dual_product {
     pub fn claim_dual_rewards(
         ctx: Context<ClaimDualRewards>,
         reward_source: RewardSource,
+        max_claim_amount: Option<u64>,
     ) -> Result<()> {
-        instructions::rewards::claim_dual_rewards(ctx, reward_source)
+        instructions::rewards::claim_dual_rewards(ctx, reward_source, max_claim_amount)
+    }
+
+    pub fn preview_dual_rewards(
+        ctx: Context<PreviewDualRewards>,
+        reward_source: RewardSource,
+    ) -> Result<()> {
+        instructions::rewards::preview_dual_rewards(ctx, reward_source)
     }
 
     pub fn update_ratios(
@@ -70,6 +96,62 @@ pub mod dual_product {
         instructions::admin::update_ratios(ctx, new_lst_ratio, new_usdc_ratio)
     }
 
+    pub fn set_fee_model(
+        ctx: Context<SetFeeModel>,
+        fee_model: FeeModel,
+    ) -> Result<()> {
+        instructions::admin::set_fee_model(ctx, fee_model)
+    }
+
+    pub fn set_min_lp_position_value(
+        ctx: Context<SetMinLPPositionValue>,
+        min_lp_position_value: u64,
+    ) -> Result<()> {
+        instructions::admin::set_min_lp_position_value(ctx, min_lp_position_value)
+    }
+
+    pub fn set_dust_threshold(
+        ctx: Context<SetDustThreshold>,
+        dust_threshold: u64,
+    ) -> Result<()> {
+        instructions::admin::set_dust_threshold(ctx, dust_threshold)
+    }
+
+    pub fn set_reward_rates(
+        ctx: Context<SetRewardRates>,
+        lst_apy_points: u16,
+        usdc_apy_points: u16,
+    ) -> Result<()> {
+        instructions::admin::set_reward_rates(ctx, lst_apy_points, usdc_apy_points)
+    }
+
+    pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+        instructions::admin::migrate_pool_state(ctx)
+    }
+
+    pub fn set_treasury(ctx: Context<SetTreasury>) -> Result<()> {
+        instructions::admin::set_treasury(ctx)
+    }
+
+    pub fn set_protocol_fee_schedule(
+        ctx: Context<SetProtocolFeeSchedule>,
+        tiers: Vec<FeeTier>,
+    ) -> Result<()> {
+        instructions::admin::set_protocol_fee_schedule(ctx, tiers)
+    }
+
+    pub fn set_fee_discount_for_long_term_stakers(
+        ctx: Context<SetFeeDiscountForLongTermStakers>,
+        min_tenure_days: u32,
+        discount_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_fee_discount_for_long_term_stakers(ctx, min_tenure_days, discount_bps)
+    }
+
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, flags: u8) -> Result<()> {
+        instructions::admin::set_pause_flags(ctx, flags)
+    }
+
     pub fn pause_product(ctx: Context<PauseProduct>) -> Result<()> {
         instructions::admin::pause_product(ctx)
     }
@@ -1,14 +1,40 @@
 use anchor_lang::prelude::*;
 
+// Bit positions in pause_flags
+pub const DEPOSIT_BIT: u8 = 0;
+pub const WITHDRAWAL_BIT: u8 = 1;
+pub const REWARD_CLAIM_BIT: u8 = 5;
+// Every defined bit; set_pause_flags rejects the rest
+pub const PAUSE_FLAGS_MASK: u8 = (1 << DEPOSIT_BIT) | (1 << WITHDRAWAL_BIT) | (1 << REWARD_CLAIM_BIT);
+
+// Volume-based fee tiers
+pub const MAX_FEE_TIERS: usize = 4;
+pub const VOLUME_WINDOW_SLOTS: u64 = 6_480_000;  // ~30 days at 400ms slots
+
+// Ceiling on the long-term staker fee discount
+pub const MAX_LOYALTY_DISCOUNT_BPS: u16 = 5000;
+
 #[account]
 pub struct DualProductConfig {
     pub authority: Pubkey,
     pub treasury: Pubkey,
     pub platform_fee_bps: u16,
-    pub min_deposit_amount: u64,
+    pub min_deposit_amount: u64,  // Minimum LST + USDC for deposit_dual, at the larger mint decimals
     pub lst_ratio: u16,  // Ratio of LST in basis points (e.g., 5000 = 50%)
     pub usdc_ratio: u16, // Ratio of USDC in basis points
+    pub fee_model: FeeModel,  // Fee applied on withdraw_dual
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],  // Sorted by descending volume_threshold
+    pub fee_tier_count: u8,
+    pub loyalty_min_tenure_days: u32,  // Tenure required for the loyalty discount
+    pub loyalty_discount_bps: u16,     // Subtracted from the withdrawal fee rate
+    pub min_lp_position_value: u64,  // Minimum LST + USDC to enter LP, at the larger mint decimals
+    pub dust_threshold: u64,  // Claims worth less than this are deferred, at the larger mint decimals
+    pub lst_reward_mint: Pubkey,
+    pub usdc_reward_mint: Pubkey,
+    pub lst_decimals: u8,   // Mint decimals of each leg, for LP share math
+    pub usdc_decimals: u8,
     pub paused: bool,
+    pub pause_flags: u8,  // Per-operation pause bits, see *_BIT
     pub bump: u8,
 }
 
@@ -17,23 +43,68 @@ pub struct UserDualPosition {
     pub owner: Pubkey,
     pub lst_amount: u64,
     pub usdc_amount: u64,
+    pub lst_principal: u64,   // Deposited principal left in each leg, the
+    pub usdc_principal: u64,  // cost basis for ProfitOnly withdrawal fees
     pub in_lp: bool,      // Whether position is in LP pool
     pub deposit_timestamp: i64,
     pub last_reward_claim: i64,
     pub bump: u8,
 }
 
+#[account]
+pub struct UserLoyaltyProfile {
+    pub user: Pubkey,
+    pub first_stake_timestamp: i64,  // Zero while the user holds no position
+    pub consecutive_staking_days: u32,
+    pub bump: u8,
+}
+
+#[account]
+pub struct UserVolumeTracker {
+    pub user: Pubkey,
+    pub rolling_30d_volume: u64,
+    pub last_update_slot: u64,
+    pub bump: u8,
+}
+
+// Scale of the per-share reward indexes
+pub const REWARD_INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+
 #[account]
 pub struct PoolState {
     pub total_lst: u64,
     pub total_usdc: u64,
     pub total_shares: u64,
-    pub lst_per_share: u64,  // Multiplied by 1e9
-    pub usdc_per_share: u64, // Multiplied by 1e9
+    pub lst_per_share: u128,  // LST reward per unit per second, multiplied by 1e18
+    pub usdc_per_share: u128, // USDC reward per unit per second, multiplied by 1e18
     pub last_update: i64,
     pub bump: u8,
 }
 
+// Pre-migration PoolState layout with u64 indexes scaled by 1e9
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PoolStateV1 {
+    pub total_lst: u64,
+    pub total_usdc: u64,
+    pub total_shares: u64,
+    pub lst_per_share: u64,
+    pub usdc_per_share: u64,
+    pub last_update: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FeeModel {
+    FlatWithdrawal { fee_bps: u16 },  // Fee on the full withdrawn amount
+    ProfitOnly { fee_bps: u16 },      // Fee on gains above principal only
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeTier {
+    pub volume_threshold: u64,  // Minimum rolling 30-day LST + USDC volume, at the larger mint decimals
+    pub fee_bps: u16,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum RewardSource {
     LST,
@@ -46,7 +117,7 @@ pub struct DualConfig {
     pub authority: Pubkey,
     pub treasury: Pubkey,
     pub platform_fee_bps: u16,
-    pub min_dual_amount: u64,
+    pub min_dual_amount: u64,  // Minimum WSOL + USDC per deposit, at the larger mint decimals
     pub total_dual_positions: u64,
     pub users_count: u64,
     pub paused: bool,
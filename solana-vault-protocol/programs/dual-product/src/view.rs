@@ -0,0 +1,4 @@
+// Payload versions for this program's views, wrapped in a
+// vault_math::view::ViewResponse
+// PreviewDualRewards: DualRewardsPreview
+pub const PREVIEW_DUAL_REWARDS_VIEW_VERSION: u8 = 1;
@@ -43,4 +43,52 @@ pub enum LockingVaultError {
     
     #[msg("Insufficient balance for operation")]
     InsufficientBalance,
-}
\ No newline at end of file
+
+    #[msg("Invalid reward emission schedule")]
+    InvalidEmissionSchedule,
+
+    #[msg("Lock duration must be greater than zero")]
+    ZeroLockDuration,
+
+    #[msg("Invalid fee configuration")]
+    InvalidFee,
+
+    #[msg("Operation is paused")]
+    OperationPaused,
+
+    #[msg("Invalid token account owner")]
+    InvalidTokenAccountOwner,
+
+    #[msg("Invalid custom reward schedule")]
+    InvalidCustomRewardSchedule,
+
+    #[msg("Lock pool is full")]
+    LockPoolFull,
+
+    #[msg("Debit would leave the account below rent exemption")]
+    RentExemptionViolation,
+
+    #[msg("Treasury cannot be the default pubkey")]
+    InvalidTreasury,
+
+    #[msg("Position is above the dust threshold")]
+    PositionNotDust,
+
+    #[msg("Unsupported view response version")]
+    UnsupportedViewVersion,
+
+    #[msg("Protocol is globally paused")]
+    GloballyPaused,
+
+    #[msg("Emission schedule account is required")]
+    MissingEmissionSchedule,
+
+    #[msg("Existing custom reward schedule is still accruing or holds unclaimed bonus")]
+    CustomRewardScheduleActive,
+
+    #[msg("Position has not been abandoned long enough to force close")]
+    PositionNotAbandoned,
+
+    #[msg("Pause flags set an undefined bit")]
+    InvalidPauseFlags,
+}
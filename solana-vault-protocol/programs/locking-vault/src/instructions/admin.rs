@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
-use crate::state::{LockingVaultConfig, LockPoolState};
+use anchor_spl::token::{Token, TokenAccount, Transfer};
+use crate::state::{LockingVaultConfig, UserLockPosition, LockPoolState, LockSolVault, AssetType, RewardEmissionSchedule, CustomUserRewardSchedule, EmissionRate, LargeDepositorBoost, MAX_EMISSION_ENTRIES, PAUSE_FLAGS_MASK};
 use crate::errors::LockingVaultError;
+use vault_sol::state::VaultConfig;
+use vault_sol::utils::debit_lamports_within_rent;
+use crate::instructions::rewards::accrued_custom_bonus;
 
 #[derive(Accounts)]
 pub struct InitializeVault<'info> {
@@ -13,6 +17,14 @@ pub struct InitializeVault<'info> {
     )]
     pub config: Account<'info, LockingVaultConfig>,
 
+    // Protocol-wide fee limit
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+
     #[account(
         init,
         payer = authority,
@@ -21,6 +33,15 @@ pub struct InitializeVault<'info> {
         bump
     )]
     pub pool_state: Account<'info, LockPoolState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<LockSolVault>(),
+        seeds = [b"lock_sol_vault"],
+        bump
+    )]
+    pub sol_vault: Account<'info, LockSolVault>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -63,6 +84,257 @@ pub struct UpdateBaseAPY<'info> {
     pub config: Account<'info, LockingVaultConfig>,
 }
 
+#[derive(Accounts)]
+pub struct SetEarlyClaimPenalty<'info> {
+    #[account(
+        mut,
+        seeds = [b"lock_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, LockPoolState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxTotalLocked<'info> {
+    #[account(
+        mut,
+        seeds = [b"lock_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, LockPoolState>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardEmissionSchedule<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"lock_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, LockPoolState>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RewardEmissionSchedule>(),
+        seeds = [b"reward_emission_schedule"],
+        bump
+    )]
+    pub emission_schedule: Account<'info, RewardEmissionSchedule>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetDustThreshold<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardBoostForLargeDepositors<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GrantPositionBoost<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"user_lock_position", user_position.owner.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserLockPosition>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct SetCustomRewardScheduleForUser<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+
+    #[account(
+        seeds = [b"lock_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, LockPoolState>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<CustomUserRewardSchedule>(),
+        seeds = [b"custom_reward_schedule", user.as_ref()],
+        bump
+    )]
+    pub custom_reward_schedule: Account<'info, CustomUserRewardSchedule>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveCustomRewardSchedule<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"custom_reward_schedule", custom_reward_schedule.user.as_ref()],
+        bump = custom_reward_schedule.bump,
+    )]
+    pub custom_reward_schedule: Account<'info, CustomUserRewardSchedule>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ForceClosePosition<'info> {
+    #[account(
+        has_one = authority,
+        has_one = treasury,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+
+    #[account(
+        mut,
+        close = treasury,
+        seeds = [b"user_lock_position", user_position.owner.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserLockPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"lock_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, LockPoolState>,
+
+    #[account(mut)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"lock_sol_vault"],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, LockSolVault>,
+
+    // USDC positions only: the residual principal moves to the treasury
+    #[account(mut)]
+    pub vault_usdc_account: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub treasury_usdc_account: Option<Account<'info, TokenAccount>>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetForceCloseParams<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxRewardClaimInterval<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct PauseVault<'info> {
     #[account(
@@ -96,20 +368,13 @@ pub fn initialize_vault(
     lock_periods: [u16; 5],
     multipliers: [u16; 5],
 ) -> Result<()> {
-    require!(platform_fee_bps <= 10000, LockingVaultError::InvalidAmount);
+    require!(platform_fee_bps <= ctx.accounts.vault_config.max_pool_fee_bps, LockingVaultError::InvalidFee);
     require!(min_deposit > 0, LockingVaultError::InvalidAmount);
-    
-    // Validate lock periods are in ascending order and multipliers
-    for i in 1..5 {
-        require!(
-            lock_periods[i] > lock_periods[i-1],
-            LockingVaultError::InvalidLockPeriods
-        );
-        require!(
-            multipliers[i] > multipliers[i-1],
-            LockingVaultError::InvalidMultiplier
-        );
-    }
+    require!(
+        ctx.accounts.treasury.key() != Pubkey::default(),
+        LockingVaultError::InvalidTreasury
+    );
+    validate_lock_periods(&lock_periods, &multipliers)?;
 
     // Initialize config
     let config = &mut ctx.accounts.config;
@@ -121,7 +386,17 @@ pub fn initialize_vault(
     config.min_deposit_amount = min_deposit;
     config.available_lock_periods = lock_periods;
     config.lock_period_multipliers = multipliers;
+    config.dust_threshold = 1000;
+    config.large_depositor_boost = LargeDepositorBoost {
+        threshold_amount: u64::MAX,  // Disabled until configured
+        boost_bps: 0,
+    };
+    config.max_combined_boost_bps = u16::MAX;
+    config.max_claim_interval_seconds = u64::MAX;  // Uncapped until configured
+    config.force_close_dust_threshold = 0;  // Force close disabled until configured
+    config.abandonment_period_seconds = i64::MAX;
     config.paused = false;
+    config.pause_flags = 0;
     config.bump = config_bump;
 
     // Initialize pool state
@@ -132,9 +407,17 @@ pub fn initialize_vault(
     pool_state.total_usdc_locked = 0;
     pool_state.base_apy_points = 500;  // Start with 5% base APY
     pool_state.total_penalties = 0;
+    pool_state.forfeited_sol_rewards = 0;
+    pool_state.forfeited_usdc_rewards = 0;
+    pool_state.max_sol_locked = 0;
+    pool_state.max_usdc_locked = 0;
+    pool_state.positions_count = 0;
+    pool_state.emission_schedule = Pubkey::default();
     pool_state.last_update = Clock::get()?.unix_timestamp;
     pool_state.bump = pool_bump;
 
+    ctx.accounts.sol_vault.bump = *ctx.bumps.get("sol_vault").unwrap();
+
     Ok(())
 }
 
@@ -143,18 +426,8 @@ pub fn update_lock_periods(
     new_periods: [u16; 5],
     new_multipliers: [u16; 5],
 ) -> Result<()> {
-    // Validate new periods are in ascending order and multipliers
-    for i in 1..5 {
-        require!(
-            new_periods[i] > new_periods[i-1],
-            LockingVaultError::InvalidLockPeriods
-        );
-        require!(
-            new_multipliers[i] > new_multipliers[i-1],
-            LockingVaultError::InvalidMultiplier
-        );
-    }
-    
+    validate_lock_periods(&new_periods, &new_multipliers)?;
+
     let config = &mut ctx.accounts.config;
     config.available_lock_periods = new_periods;
     config.lock_period_multipliers = new_multipliers;
@@ -175,6 +448,289 @@ pub fn update_base_apy(
     Ok(())
 }
 
+pub fn set_early_claim_penalty(
+    ctx: Context<SetEarlyClaimPenalty>,
+    early_claim_penalty_bps: u16,
+) -> Result<()> {
+    require!(early_claim_penalty_bps <= 10000, LockingVaultError::InvalidAmount);
+
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.early_claim_penalty_bps = early_claim_penalty_bps;
+    pool_state.last_update = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+pub fn set_max_total_locked(
+    ctx: Context<SetMaxTotalLocked>,
+    max_sol_locked: u64,
+    max_usdc_locked: u64,
+) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.max_sol_locked = max_sol_locked;
+    pool_state.max_usdc_locked = max_usdc_locked;
+    pool_state.last_update = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+pub fn set_reward_emission_schedule(
+    ctx: Context<SetRewardEmissionSchedule>,
+    entries: Vec<EmissionRate>,
+) -> Result<()> {
+    require!(
+        entries.len() <= MAX_EMISSION_ENTRIES,
+        LockingVaultError::InvalidEmissionSchedule
+    );
+
+    // Entries must be strictly increasing in time
+    for window in entries.windows(2) {
+        require!(
+            window[0].start_timestamp < window[1].start_timestamp,
+            LockingVaultError::InvalidEmissionSchedule
+        );
+    }
+    for entry in entries.iter() {
+        require!(entry.base_apy_points <= 10000, LockingVaultError::InvalidAmount); // Max 100% APY
+    }
+
+    let emission_schedule = &mut ctx.accounts.emission_schedule;
+    emission_schedule.entries = [EmissionRate::default(); MAX_EMISSION_ENTRIES];
+    emission_schedule.entries[..entries.len()].copy_from_slice(&entries);
+    emission_schedule.entry_count = entries.len() as u8;
+    emission_schedule.bump = *ctx.bumps.get("emission_schedule").unwrap();
+
+    // Claims must load the schedule from now on
+    ctx.accounts.pool_state.emission_schedule = emission_schedule.key();
+
+    Ok(())
+}
+
+pub fn set_dust_threshold(
+    ctx: Context<SetDustThreshold>,
+    dust_threshold: u64,
+) -> Result<()> {
+    require!(
+        (1..=1_000_000).contains(&dust_threshold),
+        LockingVaultError::InvalidAmount
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.dust_threshold = dust_threshold;
+
+    Ok(())
+}
+
+pub fn set_reward_boost_for_large_depositors(
+    ctx: Context<SetRewardBoostForLargeDepositors>,
+    threshold_amount: u64,
+    boost_bps: u16,
+    max_combined_boost_bps: u16,
+) -> Result<()> {
+    require!(boost_bps <= 10000, LockingVaultError::InvalidAmount);
+    require!(max_combined_boost_bps >= 10000, LockingVaultError::InvalidMultiplier);
+
+    let config = &mut ctx.accounts.config;
+    config.large_depositor_boost = LargeDepositorBoost {
+        threshold_amount,
+        boost_bps,
+    };
+    config.max_combined_boost_bps = max_combined_boost_bps;
+
+    Ok(())
+}
+
+pub fn grant_position_boost(
+    ctx: Context<GrantPositionBoost>,
+    extra_bps: u16,
+    expiry: i64,
+) -> Result<()> {
+    require!(extra_bps <= 10000, LockingVaultError::InvalidMultiplier);
+    require!(
+        expiry > Clock::get()?.unix_timestamp,
+        LockingVaultError::InvalidAmount
+    );
+
+    let user_position = &mut ctx.accounts.user_position;
+    user_position.promo_boost_bps = extra_bps;
+    user_position.promo_boost_expiry = expiry;
+
+    Ok(())
+}
+
+pub fn set_custom_reward_schedule_for_user(
+    ctx: Context<SetCustomRewardScheduleForUser>,
+    user: Pubkey,
+    bonus_rate_per_second: u64,
+    start_timestamp: i64,
+    end_timestamp: i64,
+) -> Result<()> {
+    require!(
+        bonus_rate_per_second > 0 && end_timestamp > start_timestamp,
+        LockingVaultError::InvalidCustomRewardSchedule
+    );
+
+    // An existing grant can only be replaced once it has ended and been
+    // fully claimed, and the new window must not overlap it; otherwise
+    // resetting claimed_bonus would pay the same seconds twice
+    let custom_reward_schedule = &mut ctx.accounts.custom_reward_schedule;
+    if custom_reward_schedule.user != Pubkey::default() {
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            custom_reward_schedule.end_timestamp <= current_time &&
+            accrued_custom_bonus(custom_reward_schedule, current_time)? == 0 &&
+            start_timestamp >= custom_reward_schedule.end_timestamp,
+            LockingVaultError::CustomRewardScheduleActive
+        );
+    }
+
+    custom_reward_schedule.user = user;
+    custom_reward_schedule.pool = ctx.accounts.pool_state.key();
+    custom_reward_schedule.bonus_rate_per_second = bonus_rate_per_second;
+    custom_reward_schedule.start_timestamp = start_timestamp;
+    custom_reward_schedule.end_timestamp = end_timestamp;
+    custom_reward_schedule.claimed_bonus = 0;
+    custom_reward_schedule.bump = *ctx.bumps.get("custom_reward_schedule").unwrap();
+
+    Ok(())
+}
+
+// Unclaimed bonus is forfeited when the grant is removed
+pub fn remove_custom_reward_schedule(_ctx: Context<RemoveCustomRewardSchedule>) -> Result<()> {
+    Ok(())
+}
+
+// Closes a position below the force-close dust threshold whose owner has
+// not claimed for the abandonment period. The residual principal is
+// forfeited to the treasury like an early withdrawal penalty, and so is the
+// rent
+pub fn force_close_position(ctx: Context<ForceClosePosition>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let user_position = &ctx.accounts.user_position;
+    let pool_state = &mut ctx.accounts.pool_state;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    require!(
+        user_position.amount < config.force_close_dust_threshold,
+        LockingVaultError::PositionNotDust
+    );
+    require!(
+        current_time.saturating_sub(user_position.last_reward_claim) >= config.abandonment_period_seconds,
+        LockingVaultError::PositionNotAbandoned
+    );
+
+    match user_position.asset_type {
+        AssetType::SOL => {
+            debit_lamports_within_rent(
+                &ctx.accounts.sol_vault.to_account_info(),
+                user_position.amount,
+                &Rent::get()?,
+                LockingVaultError::InsufficientBalance,
+                LockingVaultError::RentExemptionViolation,
+            )?;
+            **ctx.accounts.treasury.try_borrow_mut_lamports()? = ctx
+                .accounts
+                .treasury
+                .lamports()
+                .checked_add(user_position.amount)
+                .ok_or(LockingVaultError::MathOverflow)?;
+
+            pool_state.total_sol_locked = pool_state.total_sol_locked
+                .checked_sub(user_position.amount)
+                .ok_or(LockingVaultError::MathOverflow)?;
+        },
+        AssetType::USDC => {
+            // Validate USDC accounts are provided
+            require!(
+                ctx.accounts.vault_usdc_account.is_some() &&
+                ctx.accounts.treasury_usdc_account.is_some(),
+                LockingVaultError::InvalidTokenAccount
+            );
+            let vault_usdc_account = ctx.accounts.vault_usdc_account.as_ref().unwrap();
+            let treasury_usdc_account = ctx.accounts.treasury_usdc_account.as_ref().unwrap();
+            require_keys_eq!(
+                vault_usdc_account.owner,
+                config.key(),
+                LockingVaultError::InvalidTokenAccountOwner
+            );
+            require_keys_eq!(
+                treasury_usdc_account.owner,
+                config.treasury,
+                LockingVaultError::InvalidTokenAccountOwner
+            );
+            require_keys_eq!(
+                treasury_usdc_account.mint,
+                vault_usdc_account.mint,
+                LockingVaultError::InvalidTokenAccount
+            );
+
+            if user_position.amount > 0 {
+                let config_seeds: &[&[u8]] = &[b"locking_vault_config", &[config.bump]];
+                anchor_spl::token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: vault_usdc_account.to_account_info(),
+                            to: treasury_usdc_account.to_account_info(),
+                            authority: config.to_account_info(),
+                        },
+                        &[config_seeds],
+                    ),
+                    user_position.amount,
+                )?;
+            }
+
+            pool_state.total_usdc_locked = pool_state.total_usdc_locked
+                .checked_sub(user_position.amount)
+                .ok_or(LockingVaultError::MathOverflow)?;
+        },
+    }
+
+    pool_state.total_penalties = pool_state.total_penalties
+        .checked_add(user_position.amount)
+        .ok_or(LockingVaultError::MathOverflow)?;
+    pool_state.positions_count = pool_state.positions_count
+        .checked_sub(1)
+        .ok_or(LockingVaultError::MathOverflow)?;
+    pool_state.last_update = current_time;
+
+    Ok(())
+}
+
+pub fn set_force_close_params(
+    ctx: Context<SetForceCloseParams>,
+    force_close_dust_threshold: u64,
+    abandonment_period_seconds: i64,
+) -> Result<()> {
+    require!(abandonment_period_seconds > 0, LockingVaultError::InvalidAmount);
+
+    let config = &mut ctx.accounts.config;
+    config.force_close_dust_threshold = force_close_dust_threshold;
+    config.abandonment_period_seconds = abandonment_period_seconds;
+
+    Ok(())
+}
+
+pub fn set_max_reward_claim_interval(
+    ctx: Context<SetMaxRewardClaimInterval>,
+    max_interval_seconds: u64,
+) -> Result<()> {
+    require!(max_interval_seconds > 0, LockingVaultError::InvalidAmount);
+
+    let config = &mut ctx.accounts.config;
+    config.max_claim_interval_seconds = max_interval_seconds;
+
+    Ok(())
+}
+
+pub fn set_pause_flags(ctx: Context<SetPauseFlags>, flags: u8) -> Result<()> {
+    require!(flags & !PAUSE_FLAGS_MASK == 0, LockingVaultError::InvalidPauseFlags);
+
+    let config = &mut ctx.accounts.config;
+    config.pause_flags = flags;
+    Ok(())
+}
+
 pub fn pause_vault(ctx: Context<PauseVault>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = true;
@@ -185,4 +741,67 @@ pub fn unpause_vault(ctx: Context<UnpauseVault>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = false;
     Ok(())
-}
\ No newline at end of file
+}
+
+// Helper function to validate lock periods are non-zero and ascending, with
+// ascending multipliers
+fn validate_lock_periods(
+    lock_periods: &[u16; 5],
+    multipliers: &[u16; 5],
+) -> Result<()> {
+    // Ascending order makes the first period the shortest
+    require!(lock_periods[0] > 0, LockingVaultError::ZeroLockDuration);
+
+    for i in 1..5 {
+        require!(
+            lock_periods[i] > lock_periods[i-1],
+            LockingVaultError::InvalidLockPeriods
+        );
+        require!(
+            multipliers[i] > multipliers[i-1],
+            LockingVaultError::InvalidMultiplier
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MULTIPLIERS: [u16; 5] = [10000, 11000, 12000, 15000, 20000];
+
+    #[test]
+    fn ascending_periods_and_multipliers_are_accepted() {
+        assert!(validate_lock_periods(&[30, 60, 90, 180, 365], &MULTIPLIERS).is_ok());
+    }
+
+    #[test]
+    fn zero_shortest_period_is_rejected() {
+        assert_eq!(
+            validate_lock_periods(&[0, 60, 90, 180, 365], &MULTIPLIERS).unwrap_err(),
+            LockingVaultError::ZeroLockDuration.into()
+        );
+    }
+
+    #[test]
+    fn periods_out_of_order_are_rejected() {
+        assert_eq!(
+            validate_lock_periods(&[30, 90, 60, 180, 365], &MULTIPLIERS).unwrap_err(),
+            LockingVaultError::InvalidLockPeriods.into()
+        );
+        assert_eq!(
+            validate_lock_periods(&[30, 60, 60, 180, 365], &MULTIPLIERS).unwrap_err(),
+            LockingVaultError::InvalidLockPeriods.into()
+        );
+    }
+
+    #[test]
+    fn multipliers_out_of_order_are_rejected() {
+        assert_eq!(
+            validate_lock_periods(&[30, 60, 90, 180, 365], &[10000, 11000, 11000, 15000, 20000]).unwrap_err(),
+            LockingVaultError::InvalidMultiplier.into()
+        );
+    }
+}
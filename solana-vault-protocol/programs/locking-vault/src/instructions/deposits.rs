@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
-use crate::state::{LockingVaultConfig, UserLockPosition, LockPoolState, AssetType};
+use crate::state::{LockingVaultConfig, UserLockPosition, LockPoolState, LockSolVault, AssetType, LOCK_CREATION_BIT};
 use crate::errors::LockingVaultError;
+use vault_sol::state::GlobalPause;
+use crate::view::POSITION_COUNT_VIEW_VERSION;
+use anchor_lang::solana_program::program::set_return_data;
+use vault_math::view::encode_view_response;
 
 #[derive(Accounts)]
 pub struct CreateLockPosition<'info> {
@@ -9,9 +13,18 @@ pub struct CreateLockPosition<'info> {
         seeds = [b"locking_vault_config"],
         bump = config.bump,
         constraint = !config.paused @ LockingVaultError::VaultPaused,
+        constraint = config.pause_flags & (1 << LOCK_CREATION_BIT) == 0 @ LockingVaultError::OperationPaused,
     )]
     pub config: Account<'info, LockingVaultConfig>,
 
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ LockingVaultError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         init,
         payer = user,
@@ -38,14 +51,27 @@ pub struct CreateLockPosition<'info> {
     #[account(mut)]
     pub vault_usdc_account: Option<Account<'info, TokenAccount>>,
 
-    #[account(mut)]
-    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"lock_sol_vault"],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, LockSolVault>,
     
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct GetPositionCount<'info> {
+    #[account(
+        seeds = [b"lock_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, LockPoolState>,
+}
+
 pub fn create_lock_position(
     ctx: Context<CreateLockPosition>,
     amount: u64,
@@ -59,6 +85,7 @@ pub fn create_lock_position(
 
     // Validate amount and lock period
     require!(amount > 0, LockingVaultError::InvalidAmount);
+    require!(lock_period > 0, LockingVaultError::ZeroLockDuration);
     require!(
         amount >= config.min_deposit_amount,
         LockingVaultError::BelowMinimumDeposit
@@ -85,12 +112,12 @@ pub fn create_lock_position(
             anchor_lang::solana_program::program::invoke(
                 &anchor_lang::solana_program::system_instruction::transfer(
                     user.key,
-                    &config.treasury,
+                    &ctx.accounts.sol_vault.key(),
                     amount,
                 ),
                 &[
                     user.to_account_info(),
-                    ctx.accounts.treasury.to_account_info(),
+                    ctx.accounts.sol_vault.to_account_info(),
                     ctx.accounts.system_program.to_account_info(),
                 ],
             )?;
@@ -98,6 +125,11 @@ pub fn create_lock_position(
             pool_state.total_sol_locked = pool_state.total_sol_locked
                 .checked_add(amount)
                 .ok_or(LockingVaultError::MathOverflow)?;
+            require!(
+                pool_state.max_sol_locked == 0 ||
+                pool_state.total_sol_locked <= pool_state.max_sol_locked,
+                LockingVaultError::LockPoolFull
+            );
         },
         AssetType::USDC => {
             // Validate USDC accounts are provided
@@ -124,6 +156,11 @@ pub fn create_lock_position(
             pool_state.total_usdc_locked = pool_state.total_usdc_locked
                 .checked_add(amount)
                 .ok_or(LockingVaultError::MathOverflow)?;
+            require!(
+                pool_state.max_usdc_locked == 0 ||
+                pool_state.total_usdc_locked <= pool_state.max_usdc_locked,
+                LockingVaultError::LockPoolFull
+            );
         },
     }
 
@@ -143,9 +180,26 @@ pub fn create_lock_position(
     user_position.unlock_timestamp = unlock_time;
     user_position.last_reward_claim = current_time;
     user_position.bump = *ctx.bumps.get("user_position").unwrap();
+    user_position.promo_boost_bps = 0;
+    user_position.promo_boost_expiry = 0;
 
     // Update pool state
+    pool_state.positions_count = pool_state.positions_count
+        .checked_add(1)
+        .ok_or(LockingVaultError::MathOverflow)?;
     pool_state.last_update = current_time;
 
+    Ok(())
+}
+
+// View instruction: returns the number of open lock positions. Positions
+// are keyed by owner, not by a sequential id, so this is a total for
+// display; clients enumerate positions with getProgramAccounts
+pub fn get_position_count(ctx: Context<GetPositionCount>) -> Result<()> {
+    let positions_count = ctx.accounts.pool_state.positions_count;
+
+    msg!("Open lock positions: {}", positions_count);
+    set_return_data(&encode_view_response(POSITION_COUNT_VIEW_VERSION, &positions_count)?);
+
     Ok(())
 }
\ No newline at end of file
@@ -1,7 +1,13 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount, Transfer};
-use crate::state::{LockingVaultConfig, UserLockPosition, LockPoolState, AssetType};
+use crate::state::{LockingVaultConfig, UserLockPosition, LockPoolState, LockSolVault, RewardEmissionSchedule, CustomUserRewardSchedule, AssetType, REWARD_CLAIM_BIT};
 use crate::errors::LockingVaultError;
+use vault_sol::state::{GlobalPause, VaultConfig};
+use vault_sol::utils::{is_fee_exempt, debit_lamports_within_rent};
+use vault_math::apply_bps;
+use crate::view::{PREVIEW_LOCK_REWARDS_VIEW_VERSION, CURRENT_EMISSION_RATE_VIEW_VERSION, OPTIMAL_LOCK_DURATION_VIEW_VERSION};
+use anchor_lang::solana_program::program::set_return_data;
+use vault_math::view::encode_view_response;
 
 #[derive(Accounts)]
 pub struct ClaimLockRewards<'info> {
@@ -9,9 +15,26 @@ pub struct ClaimLockRewards<'info> {
         seeds = [b"locking_vault_config"],
         bump = config.bump,
         constraint = !config.paused @ LockingVaultError::VaultPaused,
+        constraint = config.pause_flags & (1 << REWARD_CLAIM_BIT) == 0 @ LockingVaultError::OperationPaused,
     )]
     pub config: Account<'info, LockingVaultConfig>,
 
+    // Protocol-wide fee exemption list
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ LockingVaultError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"user_lock_position", user.key().as_ref()],
@@ -21,11 +44,27 @@ pub struct ClaimLockRewards<'info> {
     pub user_position: Account<'info, UserLockPosition>,
 
     #[account(
+        mut,
         seeds = [b"lock_pool_state"],
         bump = pool_state.bump,
     )]
     pub pool_state: Account<'info, LockPoolState>,
 
+    // Required once the pool has a schedule; see emission_schedule_for
+    #[account(
+        seeds = [b"reward_emission_schedule"],
+        bump = emission_schedule.bump,
+    )]
+    pub emission_schedule: Option<Account<'info, RewardEmissionSchedule>>,
+
+    // Only users with an admin-granted bonus pass this account
+    #[account(
+        mut,
+        seeds = [b"custom_reward_schedule", user.key().as_ref()],
+        bump = custom_reward_schedule.bump,
+    )]
+    pub custom_reward_schedule: Option<Account<'info, CustomUserRewardSchedule>>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
@@ -35,16 +74,103 @@ pub struct ClaimLockRewards<'info> {
     #[account(mut)]
     pub vault_usdc_account: Option<Account<'info, TokenAccount>>,
 
-    #[account(mut)]
-    pub treasury: SystemAccount<'info>,
+    // For SOL rewards
+    #[account(
+        mut,
+        seeds = [b"lock_sol_vault"],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, LockSolVault>,
     
     pub token_program: Program<'info, Token>,
 }
 
-pub fn claim_lock_rewards(ctx: Context<ClaimLockRewards>) -> Result<()> {
+#[derive(Accounts)]
+pub struct PreviewLockRewards<'info> {
+    #[account(
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+
+    // Protocol-wide fee exemption list
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"user_lock_position", user_position.owner.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserLockPosition>,
+
+    #[account(
+        seeds = [b"lock_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, LockPoolState>,
+
+    // Required once the pool has a schedule; see emission_schedule_for
+    #[account(
+        seeds = [b"reward_emission_schedule"],
+        bump = emission_schedule.bump,
+    )]
+    pub emission_schedule: Option<Account<'info, RewardEmissionSchedule>>,
+
+    // Only users with an admin-granted bonus pass this account
+    #[account(
+        seeds = [b"custom_reward_schedule", user_position.owner.as_ref()],
+        bump = custom_reward_schedule.bump,
+    )]
+    pub custom_reward_schedule: Option<Account<'info, CustomUserRewardSchedule>>,
+}
+
+#[derive(Accounts)]
+pub struct CalculateOptimalLockDuration<'info> {
+    #[account(
+        seeds = [b"locking_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, LockingVaultConfig>,
+
+    #[account(
+        seeds = [b"lock_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, LockPoolState>,
+
+    #[account(
+        seeds = [b"reward_emission_schedule"],
+        bump = emission_schedule.bump,
+    )]
+    pub emission_schedule: Option<Account<'info, RewardEmissionSchedule>>,
+}
+
+#[derive(Accounts)]
+pub struct GetCurrentEmissionRate<'info> {
+    #[account(
+        seeds = [b"lock_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, LockPoolState>,
+
+    #[account(
+        seeds = [b"reward_emission_schedule"],
+        bump = emission_schedule.bump,
+    )]
+    pub emission_schedule: Option<Account<'info, RewardEmissionSchedule>>,
+}
+
+pub fn claim_lock_rewards(
+    ctx: Context<ClaimLockRewards>,
+    max_claim_amount: Option<u64>,
+) -> Result<()> {
     let config = &ctx.accounts.config;
     let user_position = &mut ctx.accounts.user_position;
-    let pool_state = &ctx.accounts.pool_state;
+    let pool_state = &mut ctx.accounts.pool_state;
     
     // Calculate rewards based on staking duration
     let current_time = Clock::get()?.unix_timestamp;
@@ -54,41 +180,77 @@ pub fn claim_lock_rewards(ctx: Context<ClaimLockRewards>) -> Result<()> {
     
     require!(time_staked > 0, LockingVaultError::InvalidAmount);
 
-    // Calculate rewards based on amount, time, base APY, and position multiplier
-    let rewards = calculate_lock_rewards(
-        user_position.amount,
-        time_staked,
-        pool_state.base_apy_points,
-        user_position.apy_multiplier,
+    let emission_schedule = emission_schedule_for(pool_state, ctx.accounts.emission_schedule.as_ref())?;
+    let accrual_start = capped_accrual_start(config, user_position, current_time);
+    let platform_fee_bps = platform_fee_bps_for(config, &ctx.accounts.vault_config, ctx.accounts.user.key);
+    let reward_amount = calculate_claimable_rewards(
+        config,
+        pool_state,
+        emission_schedule,
+        user_position,
+        accrual_start,
+        current_time,
+        platform_fee_bps,
     )?;
 
-    // Apply platform fee
-    let fee_amount = (rewards as u128)
-        .checked_mul(config.platform_fee_bps as u128)
-        .ok_or(LockingVaultError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(LockingVaultError::MathOverflow)? as u64;
+    let accrued_bonus = custom_bonus_for(
+        ctx.accounts.custom_reward_schedule.as_deref(),
+        pool_state.key(),
+        current_time,
+    )?;
+    // Leave dust to keep accruing instead of paying it out
+    let LockClaim { bonus_amount, payout_amount, next_claim_timestamp } = match settle_claim(
+        config,
+        reward_amount,
+        accrued_bonus,
+        max_claim_amount,
+        accrual_start,
+        current_time,
+    )? {
+        Some(claim) => claim,
+        None => return Ok(()),
+    };
 
-    let reward_amount = rewards
-        .checked_sub(fee_amount)
-        .ok_or(LockingVaultError::MathOverflow)?;
+    // Rewards beyond the max claim interval stay in the vault, where they
+    // fund later claims; record them per asset for reporting
+    if accrual_start > user_position.last_reward_claim {
+        let uncapped_amount = calculate_claimable_rewards(
+            config,
+            pool_state,
+            emission_schedule,
+            user_position,
+            user_position.last_reward_claim,
+            current_time,
+            platform_fee_bps,
+        )?;
+        let forfeited_amount = uncapped_amount.saturating_sub(reward_amount);
+
+        let forfeited_rewards = match user_position.asset_type {
+            AssetType::SOL => &mut pool_state.forfeited_sol_rewards,
+            AssetType::USDC => &mut pool_state.forfeited_usdc_rewards,
+        };
+        *forfeited_rewards = forfeited_rewards
+            .checked_add(forfeited_amount)
+            .ok_or(LockingVaultError::MathOverflow)?;
+    }
 
     // Process rewards based on asset type
     match user_position.asset_type {
         AssetType::SOL => {
             // Transfer SOL rewards to user
-            **ctx.accounts.treasury.try_borrow_mut_lamports()? = ctx
-                .accounts
-                .treasury
-                .lamports()
-                .checked_sub(reward_amount)
-                .ok_or(LockingVaultError::MathOverflow)?;
+            debit_lamports_within_rent(
+                &ctx.accounts.sol_vault.to_account_info(),
+                payout_amount,
+                &Rent::get()?,
+                LockingVaultError::InsufficientBalance,
+                LockingVaultError::RentExemptionViolation,
+            )?;
 
             **ctx.accounts.user.try_borrow_mut_lamports()? = ctx
                 .accounts
                 .user
                 .lamports()
-                .checked_add(reward_amount)
+                .checked_add(payout_amount)
                 .ok_or(LockingVaultError::MathOverflow)?;
         },
         AssetType::USDC => {
@@ -98,6 +260,17 @@ pub fn claim_lock_rewards(ctx: Context<ClaimLockRewards>) -> Result<()> {
                 ctx.accounts.vault_usdc_account.is_some(),
                 LockingVaultError::InvalidTokenAccount
             );
+            let user_usdc_account = ctx.accounts.user_usdc_account.as_ref().unwrap();
+            require_keys_eq!(
+                user_usdc_account.owner,
+                ctx.accounts.user.key(),
+                LockingVaultError::InvalidTokenAccountOwner
+            );
+            require_keys_eq!(
+                user_usdc_account.mint,
+                ctx.accounts.vault_usdc_account.as_ref().unwrap().mint,
+                LockingVaultError::InvalidTokenAccount
+            );
 
             // Transfer USDC rewards
             anchor_spl::token::transfer(
@@ -105,21 +278,450 @@ pub fn claim_lock_rewards(ctx: Context<ClaimLockRewards>) -> Result<()> {
                     ctx.accounts.token_program.to_account_info(),
                     Transfer {
                         from: ctx.accounts.vault_usdc_account.as_ref().unwrap().to_account_info(),
-                        to: ctx.accounts.user_usdc_account.as_ref().unwrap().to_account_info(),
+                        to: user_usdc_account.to_account_info(),
                         authority: config.to_account_info(),
                     },
                 ),
-                reward_amount,
+                payout_amount,
             )?;
         },
     }
 
+    if let Some(custom_reward_schedule) = ctx.accounts.custom_reward_schedule.as_deref_mut() {
+        custom_reward_schedule.claimed_bonus = custom_reward_schedule.claimed_bonus
+            .checked_add(bonus_amount)
+            .ok_or(LockingVaultError::MathOverflow)?;
+    }
+
     // Update last claim timestamp
-    user_position.last_reward_claim = current_time;
+    user_position.last_reward_claim = next_claim_timestamp;
 
     Ok(())
 }
 
+// View instruction: returns what an uncapped claim would pay right now,
+// custom bonus included and zero for dust, without transferring anything or
+// touching the claim timestamp
+pub fn preview_lock_rewards(ctx: Context<PreviewLockRewards>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let user_position = &ctx.accounts.user_position;
+    let pool_state = &ctx.accounts.pool_state;
+    let current_time = Clock::get()?.unix_timestamp;
+    let time_staked = current_time
+        .checked_sub(user_position.last_reward_claim)
+        .ok_or(LockingVaultError::MathOverflow)?;
+
+    let payout_amount = if time_staked > 0 {
+        let accrual_start = capped_accrual_start(config, user_position, current_time);
+        let reward_amount = calculate_claimable_rewards(
+            config,
+            pool_state,
+            emission_schedule_for(pool_state, ctx.accounts.emission_schedule.as_ref())?,
+            user_position,
+            accrual_start,
+            current_time,
+            platform_fee_bps_for(config, &ctx.accounts.vault_config, &user_position.owner),
+        )?;
+        let accrued_bonus = custom_bonus_for(
+            ctx.accounts.custom_reward_schedule.as_deref(),
+            pool_state.key(),
+            current_time,
+        )?;
+
+        settle_claim(config, reward_amount, accrued_bonus, None, accrual_start, current_time)?
+            .map_or(0, |claim| claim.payout_amount)
+    } else {
+        0
+    };
+
+    set_return_data(&encode_view_response(PREVIEW_LOCK_REWARDS_VIEW_VERSION, &payout_amount)?);
+
+    Ok(())
+}
+
+// View instruction: returns the base APY (bps) in effect right now
+pub fn get_current_emission_rate(ctx: Context<GetCurrentEmissionRate>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let base_apy = current_base_apy(
+        &ctx.accounts.pool_state,
+        emission_schedule_for(&ctx.accounts.pool_state, ctx.accounts.emission_schedule.as_ref())?,
+        current_time,
+    );
+
+    msg!("Current base APY: {} bps", base_apy);
+    set_return_data(&encode_view_response(CURRENT_EMISSION_RATE_VIEW_VERSION, &base_apy)?);
+
+    Ok(())
+}
+
+// View instruction: logs the net yield of every lock period that unlocks by
+// target_unlock_timestamp and returns the best period (days)
+pub fn calculate_optimal_lock_duration(
+    ctx: Context<CalculateOptimalLockDuration>,
+    amount: u64,
+    target_unlock_timestamp: i64,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let mut best: Option<(u16, u64)> = None;
+    for (period_idx, &lock_period) in config.available_lock_periods.iter().enumerate() {
+        let lock_seconds = (lock_period as i64) * 24 * 60 * 60;  // Convert days to seconds
+        let unlock_time = current_time
+            .checked_add(lock_seconds)
+            .ok_or(LockingVaultError::MathOverflow)?;
+        if unlock_time > target_unlock_timestamp {
+            continue;
+        }
+
+        let multiplier = effective_multiplier(
+            config,
+            amount,
+            config.lock_period_multipliers[period_idx],
+        )?;
+        let rewards = integrate_lock_rewards(
+            &ctx.accounts.pool_state,
+            emission_schedule_for(&ctx.accounts.pool_state, ctx.accounts.emission_schedule.as_ref())?,
+            amount,
+            multiplier,
+            current_time,
+            unlock_time,
+        )?;
+
+        // Net of platform fee
+        let fee_amount = apply_bps(rewards, config.platform_fee_bps)
+            .ok_or(LockingVaultError::MathOverflow)?;
+        let net_yield = rewards
+            .checked_sub(fee_amount)
+            .ok_or(LockingVaultError::MathOverflow)?;
+
+        msg!("Lock period {} days: multiplier {} bps, net yield {}", lock_period, multiplier, net_yield);
+
+        match best {
+            Some((_, best_yield)) if best_yield >= net_yield => {},
+            _ => best = Some((lock_period, net_yield)),
+        }
+    }
+
+    let (optimal_period, optimal_yield) = best.ok_or(LockingVaultError::InvalidLockPeriod)?;
+    msg!("Optimal lock period: {} days, net yield {}", optimal_period, optimal_yield);
+    set_return_data(&encode_view_response(OPTIMAL_LOCK_DURATION_VIEW_VERSION, &optimal_period)?);
+
+    Ok(())
+}
+
+// Payout of a claim, split into the custom bonus it includes and the claim
+// timestamp it leaves behind
+struct LockClaim {
+    bonus_amount: u64,
+    payout_amount: u64,
+    next_claim_timestamp: i64,
+}
+
+// Regular rewards plus custom bonus a claim pays under `max_claim_amount`,
+// or None while that payout is worth less than the dust threshold. Shared by
+// claim_lock_rewards and preview_lock_rewards so the preview matches
+fn settle_claim(
+    config: &LockingVaultConfig,
+    reward_amount: u64,
+    accrued_bonus: u64,
+    max_claim_amount: Option<u64>,
+    accrual_start: i64,
+    current_time: i64,
+) -> Result<Option<LockClaim>> {
+    // A partial claim leaves the rest of the accrual window unclaimed
+    let (claimed_amount, next_claim_timestamp) = partial_claim(
+        reward_amount,
+        max_claim_amount,
+        accrual_start,
+        current_time,
+    )?;
+
+    // The cap covers the whole payout; bonus past it stays unclaimed
+    let bonus_amount = capped_bonus(accrued_bonus, claimed_amount, max_claim_amount);
+    let payout_amount = claimed_amount
+        .checked_add(bonus_amount)
+        .ok_or(LockingVaultError::MathOverflow)?;
+
+    if payout_amount < config.dust_threshold {
+        return Ok(None);
+    }
+
+    Ok(Some(LockClaim {
+        bonus_amount,
+        payout_amount,
+        next_claim_timestamp,
+    }))
+}
+
+// Unclaimed bonus under the user's custom grant, which must be for this
+// pool; zero without one
+fn custom_bonus_for(
+    custom_reward_schedule: Option<&CustomUserRewardSchedule>,
+    pool_key: Pubkey,
+    current_time: i64,
+) -> Result<u64> {
+    match custom_reward_schedule {
+        Some(custom_reward_schedule) => {
+            require_keys_eq!(
+                custom_reward_schedule.pool,
+                pool_key,
+                LockingVaultError::InvalidCustomRewardSchedule
+            );
+            accrued_custom_bonus(custom_reward_schedule, current_time)
+        },
+        None => Ok(0),
+    }
+}
+
+// Caps a claim at `max_claim_amount`, returning the amount to pay and the
+// claim timestamp that leaves the unclaimed remainder accruing. The window
+// is advanced in proportion to the amount claimed
+fn partial_claim(
+    accrued_amount: u64,
+    max_claim_amount: Option<u64>,
+    accrual_start: i64,
+    current_time: i64,
+) -> Result<(u64, i64)> {
+    match max_claim_amount {
+        Some(max_claim_amount) if max_claim_amount < accrued_amount => {
+            let elapsed = current_time
+                .checked_sub(accrual_start)
+                .ok_or(LockingVaultError::MathOverflow)?;
+            let advance = (elapsed as u128)
+                .checked_mul(max_claim_amount as u128)
+                .ok_or(LockingVaultError::MathOverflow)?
+                .checked_div(accrued_amount as u128)
+                .ok_or(LockingVaultError::MathOverflow)? as i64;
+
+            Ok((max_claim_amount, accrual_start + advance))
+        },
+        _ => Ok((accrued_amount, current_time)),
+    }
+}
+
+// Portion of the accrued bonus that fits under `max_claim_amount` once the
+// regular rewards of the claim are paid
+fn capped_bonus(accrued_bonus: u64, claimed_amount: u64, max_claim_amount: Option<u64>) -> u64 {
+    match max_claim_amount {
+        Some(max_claim_amount) => accrued_bonus.min(max_claim_amount.saturating_sub(claimed_amount)),
+        None => accrued_bonus,
+    }
+}
+
+// Bonus accrued under a custom grant and not yet claimed
+pub fn accrued_custom_bonus(
+    custom_reward_schedule: &CustomUserRewardSchedule,
+    current_time: i64,
+) -> Result<u64> {
+    let accrual_end = current_time.min(custom_reward_schedule.end_timestamp);
+    if accrual_end <= custom_reward_schedule.start_timestamp {
+        return Ok(0);
+    }
+
+    let earned_bonus = (custom_reward_schedule.bonus_rate_per_second as u128)
+        .checked_mul((accrual_end - custom_reward_schedule.start_timestamp) as u128)
+        .ok_or(LockingVaultError::MathOverflow)? as u64;
+
+    Ok(earned_bonus.saturating_sub(custom_reward_schedule.claimed_bonus))
+}
+
+// Helper function to resolve the pool's emission schedule. Once one is set
+// it must be passed, so callers cannot fall back to the static base APY
+fn emission_schedule_for<'a>(
+    pool_state: &LockPoolState,
+    emission_schedule: Option<&'a Account<RewardEmissionSchedule>>,
+) -> Result<Option<&'a RewardEmissionSchedule>> {
+    if pool_state.emission_schedule == Pubkey::default() {
+        return Ok(None);
+    }
+
+    let emission_schedule = emission_schedule.ok_or(LockingVaultError::MissingEmissionSchedule)?;
+    require_keys_eq!(
+        emission_schedule.key(),
+        pool_state.emission_schedule,
+        LockingVaultError::InvalidEmissionSchedule
+    );
+
+    Ok(Some(emission_schedule))
+}
+
+// Helper function to find the base APY active at a given time
+fn current_base_apy(
+    pool_state: &LockPoolState,
+    emission_schedule: Option<&RewardEmissionSchedule>,
+    timestamp: i64,
+) -> u16 {
+    emission_schedule
+        .and_then(|schedule| {
+            schedule.entries[..schedule.entry_count as usize]
+                .iter()
+                .rev()
+                .find(|entry| entry.start_timestamp <= timestamp)
+        })
+        .map_or(pool_state.base_apy_points, |entry| entry.base_apy_points)
+}
+
+// Helper function to start accrual no earlier than max_claim_interval_seconds
+// before now
+fn capped_accrual_start(
+    config: &LockingVaultConfig,
+    user_position: &UserLockPosition,
+    current_time: i64,
+) -> i64 {
+    let max_interval = i64::try_from(config.max_claim_interval_seconds).unwrap_or(i64::MAX);
+    std::cmp::max(
+        user_position.last_reward_claim,
+        current_time.saturating_sub(max_interval),
+    )
+}
+
+// Platform fee charged to `user`; accounts on the protocol's exemption list
+// pay none
+fn platform_fee_bps_for(config: &LockingVaultConfig, vault_config: &VaultConfig, user: &Pubkey) -> u16 {
+    if is_fee_exempt(vault_config, user) {
+        0
+    } else {
+        config.platform_fee_bps
+    }
+}
+
+// Helper function to calculate rewards owed to the user after platform fee
+fn calculate_claimable_rewards(
+    config: &LockingVaultConfig,
+    pool_state: &LockPoolState,
+    emission_schedule: Option<&RewardEmissionSchedule>,
+    user_position: &UserLockPosition,
+    accrual_start: i64,
+    current_time: i64,
+    platform_fee_bps: u16,
+) -> Result<u64> {
+    let multiplier = effective_multiplier(
+        config,
+        user_position.amount,
+        user_position.apy_multiplier,
+    )?;
+
+    let mut rewards = integrate_lock_rewards(
+        pool_state,
+        emission_schedule,
+        user_position.amount,
+        multiplier,
+        accrual_start,
+        current_time,
+    )?;
+
+    // Promotional boost is additive to the multiplier and only accrues
+    // until its expiry
+    if user_position.promo_boost_bps > 0
+        && user_position.promo_boost_expiry > accrual_start
+    {
+        let promo_rewards = integrate_lock_rewards(
+            pool_state,
+            emission_schedule,
+            user_position.amount,
+            user_position.promo_boost_bps,
+            accrual_start,
+            std::cmp::min(current_time, user_position.promo_boost_expiry),
+        )?;
+        rewards = rewards
+            .checked_add(promo_rewards)
+            .ok_or(LockingVaultError::MathOverflow)?;
+    }
+
+    // Apply platform fee
+    let fee_amount = apply_bps(rewards, platform_fee_bps)
+        .ok_or(LockingVaultError::MathOverflow)?;
+
+    let reward_amount = rewards
+        .checked_sub(fee_amount)
+        .ok_or(LockingVaultError::MathOverflow)?;
+
+    // Claims before maturity forfeit a share, which stays in the reward reserve
+    if current_time < user_position.unlock_timestamp {
+        let penalty_amount = apply_bps(reward_amount, pool_state.early_claim_penalty_bps)
+            .ok_or(LockingVaultError::MathOverflow)?;
+
+        return Ok(reward_amount
+            .checked_sub(penalty_amount)
+            .ok_or(LockingVaultError::MathOverflow)?);
+    }
+
+    Ok(reward_amount)
+}
+
+// Helper function to integrate rewards across every schedule transition
+// between two timestamps
+fn integrate_lock_rewards(
+    pool_state: &LockPoolState,
+    emission_schedule: Option<&RewardEmissionSchedule>,
+    amount: u64,
+    multiplier: u16,
+    from: i64,
+    to: i64,
+) -> Result<u64> {
+    let mut rewards: u64 = 0;
+    let mut cursor = from;
+    let mut base_apy = current_base_apy(pool_state, emission_schedule, cursor);
+
+    if let Some(schedule) = emission_schedule {
+        for entry in schedule.entries[..schedule.entry_count as usize].iter() {
+            if entry.start_timestamp >= to {
+                break;
+            }
+            if entry.start_timestamp <= cursor {
+                continue;
+            }
+
+            let segment_rewards = calculate_lock_rewards(
+                amount,
+                entry.start_timestamp - cursor,
+                base_apy,
+                multiplier,
+            )?;
+            rewards = rewards
+                .checked_add(segment_rewards)
+                .ok_or(LockingVaultError::MathOverflow)?;
+
+            cursor = entry.start_timestamp;
+            base_apy = entry.base_apy_points;
+        }
+    }
+
+    let segment_rewards = calculate_lock_rewards(
+        amount,
+        to - cursor,
+        base_apy,
+        multiplier,
+    )?;
+    let rewards = rewards
+        .checked_add(segment_rewards)
+        .ok_or(LockingVaultError::MathOverflow)?;
+
+    Ok(rewards)
+}
+
+// Helper function to stack the large depositor boost onto the lock duration
+// multiplier, capped at max_combined_boost_bps
+fn effective_multiplier(
+    config: &LockingVaultConfig,
+    amount: u64,
+    apy_multiplier: u16,
+) -> Result<u16> {
+    // Position size is evaluated at claim time
+    if amount < config.large_depositor_boost.threshold_amount {
+        return Ok(apy_multiplier);
+    }
+
+    let boosted = (apy_multiplier as u128)
+        .checked_mul(10000 + config.large_depositor_boost.boost_bps as u128)
+        .ok_or(LockingVaultError::MathOverflow)?
+        .checked_div(10000)
+        .ok_or(LockingVaultError::MathOverflow)?;
+
+    Ok(std::cmp::min(boosted, config.max_combined_boost_bps as u128) as u16)
+}
+
 // Helper function to calculate locked rewards
 fn calculate_lock_rewards(
     amount: u64,
@@ -127,21 +729,188 @@ fn calculate_lock_rewards(
     base_apy: u16,
     multiplier: u16,
 ) -> Result<u64> {
-    // Calculate effective APY with multiplier
+    // Calculate effective APY with multiplier; kept as u128 since a
+    // multiplier above 1x can push it past u16
     let effective_apy = (base_apy as u128)
         .checked_mul(multiplier as u128)
         .ok_or(LockingVaultError::MathOverflow)?
         .checked_div(10000)  // Multiplier is in bps
-        .ok_or(LockingVaultError::MathOverflow)? as u16;
+        .ok_or(LockingVaultError::MathOverflow)?;
 
     // Calculate rewards based on effective APY
     let rewards = (amount as u128)
         .checked_mul(time_staked as u128)
         .ok_or(LockingVaultError::MathOverflow)?
-        .checked_mul(effective_apy as u128)
+        .checked_mul(effective_apy)
         .ok_or(LockingVaultError::MathOverflow)?
         .checked_div(365 * 24 * 60 * 60 * 10000)  // Convert APY to per-second rate
         .ok_or(LockingVaultError::MathOverflow)? as u64;
 
     Ok(rewards)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::LargeDepositorBoost;
+
+    const YEAR: i64 = 365 * 24 * 60 * 60;
+
+    fn config() -> LockingVaultConfig {
+        LockingVaultConfig {
+            authority: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            platform_fee_bps: 0,
+            min_deposit_amount: 0,
+            available_lock_periods: [30, 60, 90, 180, 365],
+            lock_period_multipliers: [10000, 11000, 12000, 15000, 20000],
+            dust_threshold: 0,
+            large_depositor_boost: LargeDepositorBoost { threshold_amount: u64::MAX, boost_bps: 0 },
+            max_combined_boost_bps: u16::MAX,
+            max_claim_interval_seconds: u64::MAX,
+            force_close_dust_threshold: 0,
+            abandonment_period_seconds: 0,
+            paused: false,
+            pause_flags: 0,
+            bump: 255,
+        }
+    }
+
+    // 10% base APY
+    fn pool_state(early_claim_penalty_bps: u16) -> LockPoolState {
+        LockPoolState {
+            total_sol_locked: 0,
+            total_usdc_locked: 0,
+            base_apy_points: 1000,
+            last_update: 0,
+            total_penalties: 0,
+            early_claim_penalty_bps,
+            forfeited_sol_rewards: 0,
+            forfeited_usdc_rewards: 0,
+            max_sol_locked: 0,
+            max_usdc_locked: 0,
+            positions_count: 0,
+            emission_schedule: Pubkey::default(),
+            bump: 255,
+        }
+    }
+
+    // 1x multiplier position opened at time 0
+    fn position(amount: u64, unlock_timestamp: i64) -> UserLockPosition {
+        UserLockPosition {
+            owner: Pubkey::new_unique(),
+            asset_type: AssetType::SOL,
+            amount,
+            lock_period: 365,
+            apy_multiplier: 10000,
+            start_timestamp: 0,
+            unlock_timestamp,
+            last_reward_claim: 0,
+            bump: 255,
+            promo_boost_bps: 0,
+            promo_boost_expiry: 0,
+        }
+    }
+
+    fn claimable(
+        pool_state: &LockPoolState,
+        user_position: &UserLockPosition,
+        accrual_start: i64,
+        current_time: i64,
+    ) -> u64 {
+        calculate_claimable_rewards(&config(), pool_state, None, user_position, accrual_start, current_time, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn partial_claim_without_cap_claims_everything() {
+        assert_eq!(partial_claim(1_000, None, 100, 200).unwrap(), (1_000, 200));
+        assert_eq!(partial_claim(1_000, Some(5_000), 100, 200).unwrap(), (1_000, 200));
+    }
+
+    #[test]
+    fn partial_claim_advances_window_in_proportion() {
+        // A quarter of the rewards covers a quarter of the window
+        assert_eq!(partial_claim(1_000, Some(250), 100, 200).unwrap(), (250, 125));
+    }
+
+    #[test]
+    fn capped_bonus_fills_what_the_regular_claim_leaves() {
+        assert_eq!(capped_bonus(400, 1_000, None), 400);
+        assert_eq!(capped_bonus(400, 700, Some(1_000)), 300);
+        assert_eq!(capped_bonus(400, 1_000, Some(1_000)), 0);
+        assert_eq!(capped_bonus(400, 100, Some(1_000)), 400);
+    }
+
+    #[test]
+    fn capped_payout_never_exceeds_max_claim_amount() {
+        let max_claim_amount = Some(1_000);
+        let (claimed_amount, _) = partial_claim(900, max_claim_amount, 0, 100).unwrap();
+        let bonus_amount = capped_bonus(500, claimed_amount, max_claim_amount);
+        assert_eq!(claimed_amount + bonus_amount, 1_000);
+    }
+
+    #[test]
+    fn lock_rewards_allow_effective_apy_above_u16() {
+        // 50_000 points at 2x is 100_000 points, past u16::MAX
+        let year = 365 * 24 * 60 * 60;
+        assert_eq!(calculate_lock_rewards(1_000, year, 50_000, 20_000).unwrap(), 10_000);
+    }
+
+    #[test]
+    fn claims_before_maturity_forfeit_the_penalty_share() {
+        let pool_state = pool_state(2000);
+
+        // A year at 10% is 100_000; 20% of it is forfeited before unlock
+        let matures_later = position(1_000_000, 2 * YEAR);
+        assert_eq!(claimable(&pool_state, &matures_later, 0, YEAR), 80_000);
+
+        let matured = position(1_000_000, YEAR);
+        assert_eq!(claimable(&pool_state, &matured, 0, YEAR), 100_000);
+    }
+
+    #[test]
+    fn claims_pay_in_full_without_a_penalty() {
+        let user_position = position(1_000_000, 2 * YEAR);
+        assert_eq!(claimable(&pool_state(0), &user_position, 0, YEAR), 100_000);
+    }
+
+    #[test]
+    fn promo_boost_accrues_only_until_expiry() {
+        // A 1x boost for the first half of the year adds half a year of rewards
+        let mut user_position = position(1_000_000, YEAR);
+        user_position.promo_boost_bps = 10000;
+        user_position.promo_boost_expiry = YEAR / 2;
+
+        assert_eq!(claimable(&pool_state(0), &user_position, 0, YEAR), 150_000);
+        assert_eq!(claimable(&pool_state(0), &user_position, 0, YEAR / 4), 50_000);
+    }
+
+    #[test]
+    fn promo_boost_is_ignored_after_expiry() {
+        let mut user_position = position(1_000_000, YEAR);
+        user_position.promo_boost_bps = 10000;
+        user_position.promo_boost_expiry = YEAR / 2;
+
+        assert_eq!(claimable(&pool_state(0), &user_position, YEAR / 2, YEAR), 50_000);
+    }
+
+    #[test]
+    fn settled_claim_includes_the_bonus() {
+        let claim = settle_claim(&config(), 1_000, 400, None, 0, 100).unwrap().unwrap();
+        assert_eq!(claim.payout_amount, 1_400);
+        assert_eq!(claim.bonus_amount, 400);
+        assert_eq!(claim.next_claim_timestamp, 100);
+    }
+
+    #[test]
+    fn settled_claim_below_dust_is_deferred() {
+        let mut config = config();
+        config.dust_threshold = 1_000;
+
+        assert!(settle_claim(&config, 600, 399, None, 0, 100).unwrap().is_none());
+        assert!(settle_claim(&config, 600, 400, None, 0, 100).unwrap().is_some());
+        // A cap below the threshold defers the claim too
+        assert!(settle_claim(&config, 5_000, 0, Some(999), 0, 100).unwrap().is_none());
+    }
+}
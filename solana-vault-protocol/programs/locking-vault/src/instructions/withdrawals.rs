@@ -1,7 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount, Transfer};
-use crate::state::{LockingVaultConfig, UserLockPosition, LockPoolState, AssetType, WithdrawType};
+use crate::state::{LockingVaultConfig, UserLockPosition, LockPoolState, LockSolVault, AssetType, WithdrawType, WITHDRAWAL_BIT};
 use crate::errors::LockingVaultError;
+use vault_sol::state::GlobalPause;
+use vault_math::apply_bps;
+use vault_sol::utils::debit_lamports_within_rent;
 
 #[derive(Accounts)]
 pub struct WithdrawLocked<'info> {
@@ -9,9 +12,18 @@ pub struct WithdrawLocked<'info> {
         seeds = [b"locking_vault_config"],
         bump = config.bump,
         constraint = !config.paused @ LockingVaultError::VaultPaused,
+        constraint = config.pause_flags & (1 << WITHDRAWAL_BIT) == 0 @ LockingVaultError::OperationPaused,
     )]
     pub config: Account<'info, LockingVaultConfig>,
 
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ LockingVaultError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"user_lock_position", user.key().as_ref()],
@@ -36,7 +48,18 @@ pub struct WithdrawLocked<'info> {
     #[account(mut)]
     pub vault_usdc_account: Option<Account<'info, TokenAccount>>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"lock_sol_vault"],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, LockSolVault>,
+
+    // Receives early withdrawal penalties on SOL positions
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ LockingVaultError::InvalidTreasury
+    )]
     pub treasury: SystemAccount<'info>,
     
     pub system_program: Program<'info, System>,
@@ -76,11 +99,8 @@ pub fn withdraw_locked(
             );
             
             // Calculate early withdrawal penalty (20%)
-            let penalty = (amount as u128)
-                .checked_mul(2000)
-                .ok_or(LockingVaultError::MathOverflow)?
-                .checked_div(10000)
-                .ok_or(LockingVaultError::MathOverflow)? as u64;
+            let penalty = apply_bps(amount, 2000)
+                .ok_or(LockingVaultError::MathOverflow)?;
 
             let withdraw = amount
                 .checked_sub(penalty)
@@ -93,13 +113,14 @@ pub fn withdraw_locked(
     // Process withdrawal based on asset type
     match user_position.asset_type {
         AssetType::SOL => {
-            // Transfer SOL back to user
-            **ctx.accounts.treasury.try_borrow_mut_lamports()? = ctx
-                .accounts
-                .treasury
-                .lamports()
-                .checked_sub(withdrawal_amount)
-                .ok_or(LockingVaultError::MathOverflow)?;
+            // Pay the user out of the vault and any penalty to the treasury
+            debit_lamports_within_rent(
+                &ctx.accounts.sol_vault.to_account_info(),
+                amount,
+                &Rent::get()?,
+                LockingVaultError::InsufficientBalance,
+                LockingVaultError::RentExemptionViolation,
+            )?;
 
             **ctx.accounts.user.try_borrow_mut_lamports()? = ctx
                 .accounts
@@ -107,6 +128,12 @@ pub fn withdraw_locked(
                 .lamports()
                 .checked_add(withdrawal_amount)
                 .ok_or(LockingVaultError::MathOverflow)?;
+            **ctx.accounts.treasury.try_borrow_mut_lamports()? = ctx
+                .accounts
+                .treasury
+                .lamports()
+                .checked_add(penalty_amount)
+                .ok_or(LockingVaultError::MathOverflow)?;
 
             pool_state.total_sol_locked = pool_state.total_sol_locked
                 .checked_sub(amount)
@@ -119,6 +146,17 @@ pub fn withdraw_locked(
                 ctx.accounts.vault_usdc_account.is_some(),
                 LockingVaultError::InvalidTokenAccount
             );
+            let user_usdc_account = ctx.accounts.user_usdc_account.as_ref().unwrap();
+            require_keys_eq!(
+                user_usdc_account.owner,
+                ctx.accounts.user.key(),
+                LockingVaultError::InvalidTokenAccountOwner
+            );
+            require_keys_eq!(
+                user_usdc_account.mint,
+                ctx.accounts.vault_usdc_account.as_ref().unwrap().mint,
+                LockingVaultError::InvalidTokenAccount
+            );
 
             // Transfer USDC tokens back to user
             anchor_spl::token::transfer(
@@ -126,7 +164,7 @@ pub fn withdraw_locked(
                     ctx.accounts.token_program.to_account_info(),
                     Transfer {
                         from: ctx.accounts.vault_usdc_account.as_ref().unwrap().to_account_info(),
-                        to: ctx.accounts.user_usdc_account.as_ref().unwrap().to_account_info(),
+                        to: user_usdc_account.to_account_info(),
                         authority: config.to_account_info(),
                     },
                 ),
@@ -151,6 +189,15 @@ pub fn withdraw_locked(
             .ok_or(LockingVaultError::MathOverflow)?;
     }
 
+    // An empty position earns nothing more, so close it and refund the rent;
+    // rewards still unclaimed are forfeited, so claim before withdrawing
+    if user_position.amount == 0 {
+        pool_state.positions_count = pool_state.positions_count
+            .checked_sub(1)
+            .ok_or(LockingVaultError::MathOverflow)?;
+        user_position.close(ctx.accounts.user.to_account_info())?;
+    }
+
     // Update pool state
     pool_state.last_update = current_time;
 
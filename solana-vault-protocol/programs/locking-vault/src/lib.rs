@@ -3,6 +3,7 @@ use anchor_lang::prelude::*;
 pub mod errors;
 pub mod state;
 pub mod instructions;
+pub mod view;
 
 use instructions::*;
 use state::*;
@@ -32,6 +33,12 @@ pub mod locking_vault {
         instructions::deposits::create_lock_position(ctx, amount, asset_type, lock_period)
     }
 
+    pub fn get_position_count(
+        ctx: Context<GetPositionCount>,
+    ) -> Result<()> {
+        instructions::deposits::get_position_count(ctx)
+    }
+
     pub fn withdraw_locked(
         ctx: Context<WithdrawLocked>,
         amount: u64,
@@ -42,8 +49,29 @@ pub mod locking_vault {
 
     pub fn claim_lock_rewards(
         ctx: Context<ClaimLockRewards>,
+        max_claim_amount: Option<u64>,
+    ) -> Result<()> {
+        instructions::rewards::claim_lock_rewards(ctx, max_claim_amount)
+    }
+
+    pub fn preview_lock_rewards(
+        ctx: Context<PreviewLockRewards>,
     ) -> Result<()> {
-        instructions::rewards::claim_lock_rewards(ctx)
+        instructions::rewards::preview_lock_rewards(ctx)
+    }
+
+    pub fn calculate_optimal_lock_duration(
+        ctx: Context<CalculateOptimalLockDuration>,
+        amount: u64,
+        target_unlock_timestamp: i64,
+    ) -> Result<()> {
+        instructions::rewards::calculate_optimal_lock_duration(ctx, amount, target_unlock_timestamp)
+    }
+
+    pub fn get_current_emission_rate(
+        ctx: Context<GetCurrentEmissionRate>,
+    ) -> Result<()> {
+        instructions::rewards::get_current_emission_rate(ctx)
     }
 
     pub fn update_lock_periods(
@@ -61,6 +89,100 @@ pub mod locking_vault {
         instructions::admin::update_base_apy(ctx, new_base_apy)
     }
 
+    pub fn set_early_claim_penalty(
+        ctx: Context<SetEarlyClaimPenalty>,
+        early_claim_penalty_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_early_claim_penalty(ctx, early_claim_penalty_bps)
+    }
+
+    pub fn set_max_total_locked(
+        ctx: Context<SetMaxTotalLocked>,
+        max_sol_locked: u64,
+        max_usdc_locked: u64,
+    ) -> Result<()> {
+        instructions::admin::set_max_total_locked(ctx, max_sol_locked, max_usdc_locked)
+    }
+
+    pub fn set_reward_emission_schedule(
+        ctx: Context<SetRewardEmissionSchedule>,
+        entries: Vec<EmissionRate>,
+    ) -> Result<()> {
+        instructions::admin::set_reward_emission_schedule(ctx, entries)
+    }
+
+    pub fn set_dust_threshold(
+        ctx: Context<SetDustThreshold>,
+        dust_threshold: u64,
+    ) -> Result<()> {
+        instructions::admin::set_dust_threshold(ctx, dust_threshold)
+    }
+
+    pub fn set_reward_boost_for_large_depositors(
+        ctx: Context<SetRewardBoostForLargeDepositors>,
+        threshold_amount: u64,
+        boost_bps: u16,
+        max_combined_boost_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_reward_boost_for_large_depositors(
+            ctx,
+            threshold_amount,
+            boost_bps,
+            max_combined_boost_bps,
+        )
+    }
+
+    pub fn grant_position_boost(
+        ctx: Context<GrantPositionBoost>,
+        extra_bps: u16,
+        expiry: i64,
+    ) -> Result<()> {
+        instructions::admin::grant_position_boost(ctx, extra_bps, expiry)
+    }
+
+    pub fn set_custom_reward_schedule_for_user(
+        ctx: Context<SetCustomRewardScheduleForUser>,
+        user: Pubkey,
+        bonus_rate_per_second: u64,
+        start_timestamp: i64,
+        end_timestamp: i64,
+    ) -> Result<()> {
+        instructions::admin::set_custom_reward_schedule_for_user(
+            ctx,
+            user,
+            bonus_rate_per_second,
+            start_timestamp,
+            end_timestamp,
+        )
+    }
+
+    pub fn remove_custom_reward_schedule(ctx: Context<RemoveCustomRewardSchedule>) -> Result<()> {
+        instructions::admin::remove_custom_reward_schedule(ctx)
+    }
+
+    pub fn force_close_position(ctx: Context<ForceClosePosition>) -> Result<()> {
+        instructions::admin::force_close_position(ctx)
+    }
+
+    pub fn set_force_close_params(
+        ctx: Context<SetForceCloseParams>,
+        force_close_dust_threshold: u64,
+        abandonment_period_seconds: i64,
+    ) -> Result<()> {
+        instructions::admin::set_force_close_params(ctx, force_close_dust_threshold, abandonment_period_seconds)
+    }
+
+    pub fn set_max_reward_claim_interval(
+        ctx: Context<SetMaxRewardClaimInterval>,
+        max_interval_seconds: u64,
+    ) -> Result<()> {
+        instructions::admin::set_max_reward_claim_interval(ctx, max_interval_seconds)
+    }
+
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, flags: u8) -> Result<()> {
+        instructions::admin::set_pause_flags(ctx, flags)
+    }
+
     pub fn pause_vault(ctx: Context<PauseVault>) -> Result<()> {
         instructions::admin::pause_vault(ctx)
     }
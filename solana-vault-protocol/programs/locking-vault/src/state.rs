@@ -1,5 +1,12 @@
 use anchor_lang::prelude::*;
 
+// Bit positions in pause_flags
+pub const WITHDRAWAL_BIT: u8 = 1;
+pub const REWARD_CLAIM_BIT: u8 = 5;
+pub const LOCK_CREATION_BIT: u8 = 6;
+// Every defined bit; set_pause_flags rejects the rest
+pub const PAUSE_FLAGS_MASK: u8 = (1 << WITHDRAWAL_BIT) | (1 << REWARD_CLAIM_BIT) | (1 << LOCK_CREATION_BIT);
+
 #[account]
 pub struct LockingVaultConfig {
     pub authority: Pubkey,
@@ -8,7 +15,14 @@ pub struct LockingVaultConfig {
     pub min_deposit_amount: u64,
     pub available_lock_periods: [u16; 5],  // Lock periods in days [30, 90, 180, 270, 360]
     pub lock_period_multipliers: [u16; 5], // APY multipliers for each period in bps
+    pub dust_threshold: u64,     // Claims below this net amount are deferred
+    pub large_depositor_boost: LargeDepositorBoost,
+    pub max_combined_boost_bps: u16,  // Cap on lock multiplier x size boost
+    pub max_claim_interval_seconds: u64,  // Longest window a single claim accrues
+    pub force_close_dust_threshold: u64,  // Positions below this may be force closed
+    pub abandonment_period_seconds: i64,  // Claim inactivity before a force close
     pub paused: bool,
+    pub pause_flags: u8,  // Per-operation pause bits, see *_BIT
     pub bump: u8,
 }
 
@@ -23,6 +37,8 @@ pub struct UserLockPosition {
     pub unlock_timestamp: i64,
     pub last_reward_claim: i64,
     pub bump: u8,
+    pub promo_boost_bps: u16,    // Extra multiplier granted by admin, in bps
+    pub promo_boost_expiry: i64,
 }
 
 #[account]
@@ -32,6 +48,56 @@ pub struct LockPoolState {
     pub base_apy_points: u16,    // Base APY in bps before multipliers
     pub last_update: i64,
     pub total_penalties: u64,    // Early withdrawal penalties collected
+    pub early_claim_penalty_bps: u16,  // Penalty on rewards claimed before unlock
+    // Metrics only: rewards cut by the claim interval cap are never paid out,
+    // so they stay in the vault balance that funds every other claim
+    pub forfeited_sol_rewards: u64,
+    pub forfeited_usdc_rewards: u64,
+    pub max_sol_locked: u64,     // Cap on total_sol_locked, 0 = uncapped
+    pub max_usdc_locked: u64,    // Cap on total_usdc_locked, 0 = uncapped
+    pub positions_count: u64,    // Open lock positions
+    pub emission_schedule: Pubkey,  // Set once a schedule exists, default = none
+    pub bump: u8,
+}
+
+// Holds locked SOL and the SOL paid out as lock rewards. The program owns
+// it, so withdrawals and claims can debit it directly; the authority funds
+// rewards with plain transfers into it
+#[account]
+pub struct LockSolVault {
+    pub bump: u8,
+}
+
+// Admin-granted bonus paid on top of standard lock rewards
+#[account]
+pub struct CustomUserRewardSchedule {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub bonus_rate_per_second: u64,  // In the position's asset units
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub claimed_bonus: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct LargeDepositorBoost {
+    pub threshold_amount: u64,   // Position size at which the boost applies
+    pub boost_bps: u16,          // Extra APY in bps of the base rate
+}
+
+pub const MAX_EMISSION_ENTRIES: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct EmissionRate {
+    pub start_timestamp: i64,
+    pub base_apy_points: u16,    // Base APY in bps from start_timestamp onwards
+}
+
+#[account]
+pub struct RewardEmissionSchedule {
+    pub entries: [EmissionRate; MAX_EMISSION_ENTRIES],  // Sorted by start_timestamp
+    pub entry_count: u8,
     pub bump: u8,
 }
 
@@ -0,0 +1,10 @@
+// Payload versions for this program's views, wrapped in a
+// vault_math::view::ViewResponse
+// PreviewLockRewards: u64 net claimable rewards, custom bonus included
+pub const PREVIEW_LOCK_REWARDS_VIEW_VERSION: u8 = 1;
+// GetCurrentEmissionRate: u16 base APY in bps
+pub const CURRENT_EMISSION_RATE_VIEW_VERSION: u8 = 1;
+// CalculateOptimalLockDuration: u16 lock period in days
+pub const OPTIMAL_LOCK_DURATION_VIEW_VERSION: u8 = 1;
+// GetPositionCount: u64 open lock positions
+pub const POSITION_COUNT_VIEW_VERSION: u8 = 1;
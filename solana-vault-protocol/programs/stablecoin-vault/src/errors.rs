@@ -31,4 +31,34 @@ pub enum StablecoinVaultError {
 
     #[msg("Invalid lending ratio")]
     InvalidLendingRatio,
-}
\ No newline at end of file
+
+    #[msg("Account already migrated")]
+    AlreadyMigrated,
+
+    #[msg("Reward account mint does not match the configured reward mint")]
+    RewardMintMismatch,
+
+    #[msg("Invalid fee configuration")]
+    InvalidFee,
+
+    #[msg("Operation is paused")]
+    OperationPaused,
+
+    #[msg("Invalid token account owner")]
+    InvalidTokenAccountOwner,
+
+    #[msg("Timelock has not elapsed")]
+    TimelockNotElapsed,
+
+    #[msg("Treasury cannot be the default pubkey")]
+    InvalidTreasury,
+
+    #[msg("Protocol is globally paused")]
+    GloballyPaused,
+
+    #[msg("Unsupported view response version")]
+    UnsupportedViewVersion,
+
+    #[msg("Pause flags set an undefined bit")]
+    InvalidPauseFlags,
+}
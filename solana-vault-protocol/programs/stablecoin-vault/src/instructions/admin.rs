@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
-use crate::state::StablecoinVaultConfig;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{Mint, TokenAccount};
+use crate::state::{StablecoinVaultConfig, StablePoolState, StablePoolStateV1, LendingRatioProposal, FeeModel, DEFAULT_GOVERNANCE_DELAY_SLOTS, PAUSE_FLAGS_MASK, REWARD_INDEX_SCALE};
 use crate::errors::StablecoinVaultError;
+use vault_sol::state::VaultConfig;
+use vault_math::per_second_rate;
 
 #[derive(Accounts)]
 pub struct InitializeVault<'info> {
@@ -12,24 +16,115 @@ pub struct InitializeVault<'info> {
         bump
     )]
     pub config: Account<'info, StablecoinVaultConfig>,
+
+    // Protocol-wide fee limit
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<StablePoolState>(),
+        seeds = [b"stable_pool_state"],
+        bump
+    )]
+    pub pool_state: Account<'info, StablePoolState>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
     
     pub treasury: SystemAccount<'info>,
+
+    // Mint that reward claims must pay out in
+    pub reward_mint: Account<'info, Mint>,
+
+    pub usdc_mint: Account<'info, Mint>,
+
+    // Reserve that deposits, donations and withdrawals are pinned to
+    #[account(
+        token::mint = usdc_mint,
+        token::authority = config,
+    )]
+    pub vault_usdc_account: Account<'info, TokenAccount>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateLendingRatio<'info> {
+pub struct ProposeLendingRatioChange<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"stable_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, StablecoinVaultConfig>,
+
+    // One proposal may be pending at a time
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<LendingRatioProposal>(),
+        seeds = [b"lending_ratio_proposal"],
+        bump
+    )]
+    pub proposal: Account<'info, LendingRatioProposal>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteLendingRatioChange<'info> {
+    #[account(
+        seeds = [b"stable_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, StablecoinVaultConfig>,
+
     #[account(
         mut,
+        seeds = [b"stable_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, StablePoolState>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"lending_ratio_proposal"],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, LendingRatioProposal>,
+
+    // Receives the proposal rent; execution itself is permissionless
+    #[account(mut, address = config.authority)]
+    pub authority: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLendingRatioProposal<'info> {
+    #[account(
         has_one = authority,
         seeds = [b"stable_vault_config"],
         bump = config.bump,
     )]
     pub config: Account<'info, StablecoinVaultConfig>,
-    
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"lending_ratio_proposal"],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, LendingRatioProposal>,
+
+    #[account(mut)]
     pub authority: Signer<'info>,
 }
 
@@ -46,6 +141,59 @@ pub struct ToggleLending<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetLendingRewardRate<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"stable_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, StablecoinVaultConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"stable_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, StablePoolState>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeModel<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"stable_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, StablecoinVaultConfig>,
+
+    // Protocol-wide fee limit
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"stable_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, StablecoinVaultConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct PauseVault<'info> {
     #[account(
@@ -72,15 +220,43 @@ pub struct UnpauseVault<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct MigratePoolState<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"stable_vault_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, StablecoinVaultConfig>,
+
+    /// CHECK: Deserialized manually from the pre-migration layout
+    #[account(
+        mut,
+        seeds = [b"stable_pool_state"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub pool_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 pub fn initialize_vault(
     ctx: Context<InitializeVault>,
     platform_fee_bps: u16,
     min_deposit: u64,
     lending_ratio: u16,
 ) -> Result<()> {
-    require!(platform_fee_bps <= 10000, StablecoinVaultError::InvalidAmount);
+    require!(platform_fee_bps <= ctx.accounts.vault_config.max_pool_fee_bps, StablecoinVaultError::InvalidFee);
     require!(min_deposit > 0, StablecoinVaultError::InvalidAmount);
     require!(lending_ratio <= 10000, StablecoinVaultError::InvalidLendingRatio);
+    require!(
+        ctx.accounts.treasury.key() != Pubkey::default(),
+        StablecoinVaultError::InvalidTreasury
+    );
 
     let config = &mut ctx.accounts.config;
     let bump = *ctx.bumps.get("config").unwrap();
@@ -90,21 +266,62 @@ pub fn initialize_vault(
     config.platform_fee_bps = platform_fee_bps;
     config.min_deposit_amount = min_deposit;
     config.lending_enabled = false;  // Start with lending disabled
+    config.fee_model = FeeModel::FlatWithdrawal { fee_bps: platform_fee_bps };
+    config.reward_mint = ctx.accounts.reward_mint.key();
+    config.governance_delay_slots = DEFAULT_GOVERNANCE_DELAY_SLOTS;
+    config.usdc_mint = ctx.accounts.usdc_mint.key();
+    config.vault_usdc_account = ctx.accounts.vault_usdc_account.key();
     config.paused = false;
+    config.pause_flags = 0;
     config.bump = bump;
 
+    // Initialize pool state
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.total_deposits = 0;
+    pool_state.total_shares = 0;
+    pool_state.apy_points = 0;
+    pool_state.stable_per_share = 0;
+    pool_state.last_update = Clock::get()?.unix_timestamp;
+    pool_state.lending_ratio = lending_ratio;
+    pool_state.bump = *ctx.bumps.get("pool_state").unwrap();
+
     Ok(())
 }
 
-pub fn update_lending_ratio(
-    ctx: Context<UpdateLendingRatio>,
+pub fn propose_lending_ratio_change(
+    ctx: Context<ProposeLendingRatioChange>,
     new_ratio: u16,
 ) -> Result<()> {
     require!(new_ratio <= 10000, StablecoinVaultError::InvalidLendingRatio);
-    
-    let config = &mut ctx.accounts.config;
+
+    let config = &ctx.accounts.config;
     require!(config.lending_enabled, StablecoinVaultError::LendingDisabled);
-    
+
+    let proposal = &mut ctx.accounts.proposal;
+    proposal.proposed_ratio = new_ratio;
+    proposal.executable_after_slot = Clock::get()?.slot
+        .checked_add(config.governance_delay_slots)
+        .ok_or(StablecoinVaultError::MathOverflow)?;
+    proposal.bump = *ctx.bumps.get("proposal").unwrap();
+
+    Ok(())
+}
+
+pub fn execute_lending_ratio_change(ctx: Context<ExecuteLendingRatioChange>) -> Result<()> {
+    let proposal = &ctx.accounts.proposal;
+    require!(
+        Clock::get()?.slot >= proposal.executable_after_slot,
+        StablecoinVaultError::TimelockNotElapsed
+    );
+
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.lending_ratio = proposal.proposed_ratio;
+    pool_state.last_update = Clock::get()?.unix_timestamp;
+
+    Ok(())
+}
+
+pub fn cancel_lending_ratio_proposal(_ctx: Context<CancelLendingRatioProposal>) -> Result<()> {
     Ok(())
 }
 
@@ -117,6 +334,44 @@ pub fn toggle_lending(
     Ok(())
 }
 
+// Sets the lending reward index from an APY in basis points; claims pay it
+// per share for every second since the last claim
+pub fn set_lending_reward_rate(
+    ctx: Context<SetLendingRewardRate>,
+    apy_points: u16,
+) -> Result<()> {
+    require!(apy_points <= 10000, StablecoinVaultError::InvalidAmount);
+
+    let pool_state = &mut ctx.accounts.pool_state;
+    pool_state.stable_per_share = per_second_rate(apy_points, REWARD_INDEX_SCALE)
+        .ok_or(StablecoinVaultError::MathOverflow)?;
+
+    Ok(())
+}
+
+pub fn set_fee_model(
+    ctx: Context<SetFeeModel>,
+    fee_model: FeeModel,
+) -> Result<()> {
+    let fee_bps = match fee_model {
+        FeeModel::FlatWithdrawal { fee_bps } | FeeModel::ProfitOnly { fee_bps } => fee_bps,
+    };
+    require!(fee_bps <= ctx.accounts.vault_config.max_pool_fee_bps, StablecoinVaultError::InvalidFee);
+
+    let config = &mut ctx.accounts.config;
+    config.fee_model = fee_model;
+
+    Ok(())
+}
+
+pub fn set_pause_flags(ctx: Context<SetPauseFlags>, flags: u8) -> Result<()> {
+    require!(flags & !PAUSE_FLAGS_MASK == 0, StablecoinVaultError::InvalidPauseFlags);
+
+    let config = &mut ctx.accounts.config;
+    config.pause_flags = flags;
+    Ok(())
+}
+
 pub fn pause_vault(ctx: Context<PauseVault>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = true;
@@ -127,4 +382,59 @@ pub fn unpause_vault(ctx: Context<UnpauseVault>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = false;
     Ok(())
-}
\ No newline at end of file
+}
+
+// Rewrites a StablePoolState with a u64 1e9-scaled reward index into the
+// current u128 1e18-scaled layout, topping up rent for the larger account
+pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+    let pool_info = ctx.accounts.pool_state.to_account_info();
+    let new_space = 8 + std::mem::size_of::<StablePoolState>();
+
+    let legacy = {
+        let data = pool_info.try_borrow_data()?;
+        require!(data.len() < new_space, StablecoinVaultError::AlreadyMigrated);
+        require!(
+            data.len() >= 8 && data[..8] == StablePoolState::discriminator(),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        StablePoolStateV1::deserialize(&mut &data[8..])?
+    };
+
+    // Fund rent for the extra space
+    let required_lamports = Rent::get()?.minimum_balance(new_space);
+    let shortfall = required_lamports.saturating_sub(pool_info.lamports());
+    if shortfall > 0 {
+        anchor_lang::solana_program::program::invoke(
+            &anchor_lang::solana_program::system_instruction::transfer(
+                ctx.accounts.authority.key,
+                pool_info.key,
+                shortfall,
+            ),
+            &[
+                ctx.accounts.authority.to_account_info(),
+                pool_info.clone(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+
+    pool_info.realloc(new_space, false)?;
+
+    // Rescale index from 1e9 to 1e18
+    let migrated = StablePoolState {
+        total_deposits: legacy.total_deposits,
+        total_shares: legacy.total_shares,
+        apy_points: legacy.apy_points,
+        stable_per_share: (legacy.stable_per_share as u128)
+            .checked_mul(1_000_000_000)
+            .ok_or(StablecoinVaultError::MathOverflow)?,
+        last_update: legacy.last_update,
+        lending_ratio: legacy.lending_ratio,
+        bump: legacy.bump,
+    };
+
+    let mut data = pool_info.try_borrow_mut_data()?;
+    migrated.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
@@ -1,7 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount, Transfer};
-use crate::state::{StablecoinVaultConfig, UserStablePosition, StablePoolState};
+use crate::state::{StablecoinVaultConfig, UserStablePosition, StablePoolState, UserPositionLedger, LEDGER_ACCOUNT_SPACE, DEPOSIT_BIT, LEDGER_DEPOSIT};
 use crate::errors::StablecoinVaultError;
+use vault_sol::state::GlobalPause;
+use crate::math::div_floor;
+use crate::instructions::ledger::record_ledger_entry;
 
 #[derive(Accounts)]
 pub struct DepositStable<'info> {
@@ -9,9 +12,18 @@ pub struct DepositStable<'info> {
         seeds = [b"stable_vault_config"],
         bump = config.bump,
         constraint = !config.paused @ StablecoinVaultError::VaultPaused,
+        constraint = config.pause_flags & (1 << DEPOSIT_BIT) == 0 @ StablecoinVaultError::OperationPaused,
     )]
     pub config: Account<'info, StablecoinVaultConfig>,
 
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ StablecoinVaultError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         init_if_needed,
         payer = user,
@@ -28,14 +40,27 @@ pub struct DepositStable<'info> {
     )]
     pub pool_state: Account<'info, StablePoolState>,
 
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = LEDGER_ACCOUNT_SPACE,
+        seeds = [b"user_position_ledger", user.key().as_ref()],
+        bump
+    )]
+    pub ledger: Box<Account<'info, UserPositionLedger>>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
     // USDC Token accounts
+    #[account(address = config.usdc_mint @ StablecoinVaultError::InvalidTokenAccount)]
     pub usdc_mint: Account<'info, Mint>,
     #[account(mut)]
     pub user_usdc_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        address = config.vault_usdc_account @ StablecoinVaultError::InvalidTokenAccount,
+    )]
     pub vault_usdc_account: Account<'info, TokenAccount>,
 
     pub system_program: Program<'info, System>,
@@ -43,6 +68,47 @@ pub struct DepositStable<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct DonateToReserve<'info> {
+    #[account(
+        seeds = [b"stable_vault_config"],
+        bump = config.bump,
+        constraint = !config.paused @ StablecoinVaultError::VaultPaused,
+        constraint = config.pause_flags & (1 << DEPOSIT_BIT) == 0 @ StablecoinVaultError::OperationPaused,
+    )]
+    pub config: Account<'info, StablecoinVaultConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ StablecoinVaultError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
+    #[account(
+        mut,
+        seeds = [b"stable_pool_state"],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Account<'info, StablePoolState>,
+
+    pub donor: Signer<'info>,
+
+    #[account(mut)]
+    pub donor_usdc_account: Account<'info, TokenAccount>,
+    // Only transfers into the vault reserve may raise total_deposits
+    #[account(
+        mut,
+        address = config.vault_usdc_account @ StablecoinVaultError::InvalidTokenAccount,
+        token::mint = config.usdc_mint,
+        token::authority = config,
+    )]
+    pub vault_usdc_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
 pub fn deposit_stable(
     ctx: Context<DepositStable>,
     amount: u64,
@@ -77,12 +143,12 @@ pub fn deposit_stable(
         // Initial deposit
         amount
     } else {
-        // Calculate based on proportion of pool
-        (amount as u128)
+        // Calculate based on proportion of pool, rounding down so the
+        // depositor never receives more shares than they paid for
+        let numerator = (amount as u128)
             .checked_mul(pool_state.total_shares as u128)
-            .ok_or(StablecoinVaultError::MathOverflow)?
-            .checked_div(pool_state.total_deposits as u128)
-            .ok_or(StablecoinVaultError::MathOverflow)? as u64
+            .ok_or(StablecoinVaultError::MathOverflow)?;
+        div_floor(numerator, pool_state.total_deposits as u128)? as u64
     };
 
     // Initialize user position if new
@@ -109,5 +175,48 @@ pub fn deposit_stable(
         .ok_or(StablecoinVaultError::MathOverflow)?;
     pool_state.last_update = Clock::get()?.unix_timestamp;
 
+    let ledger_bump = *ctx.bumps.get("ledger").unwrap();
+    record_ledger_entry(
+        &mut ctx.accounts.ledger,
+        user.key(),
+        ledger_bump,
+        pool_state,
+        LEDGER_DEPOSIT,
+        amount,
+        0,
+    )?;
+
+    Ok(())
+}
+
+// Adds USDC to the reserve without minting shares, raising the value of
+// every existing share
+pub fn donate_to_reserve(
+    ctx: Context<DonateToReserve>,
+    amount: u64,
+) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state;
+
+    require!(amount > 0, StablecoinVaultError::InvalidAmount);
+    // A donation to an empty pool would be captured by the next depositor
+    require!(pool_state.total_shares > 0, StablecoinVaultError::InsufficientBalance);
+
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.donor_usdc_account.to_account_info(),
+                to: ctx.accounts.vault_usdc_account.to_account_info(),
+                authority: ctx.accounts.donor.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    pool_state.total_deposits = pool_state.total_deposits
+        .checked_add(amount)
+        .ok_or(StablecoinVaultError::MathOverflow)?;
+    pool_state.last_update = Clock::get()?.unix_timestamp;
+
     Ok(())
-}
\ No newline at end of file
+}
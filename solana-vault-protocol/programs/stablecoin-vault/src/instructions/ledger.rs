@@ -0,0 +1,167 @@
+use anchor_lang::prelude::*;
+use crate::state::{UserPositionLedger, LedgerEntry, StablePoolState, LEDGER_CAPACITY};
+use crate::errors::StablecoinVaultError;
+use crate::view::USER_POSITION_LEDGER_VIEW_VERSION;
+use anchor_lang::solana_program::program::set_return_data;
+use vault_math::view::encode_view_response;
+
+// Scale of the share price recorded in ledger entries
+pub const LEDGER_RATE_SCALE: u128 = 1_000_000_000;
+
+// Most entries the ledger view can return: 33-byte entries behind the view
+// envelope and vec prefixes must fit in the 1024-byte return data
+pub const LEDGER_VIEW_MAX_ENTRIES: usize = 30;
+
+#[derive(Accounts)]
+pub struct GetUserPositionLedger<'info> {
+    #[account(
+        seeds = [b"user_position_ledger", ledger.owner.as_ref()],
+        bump = ledger.bump,
+    )]
+    pub ledger: Box<Account<'info, UserPositionLedger>>,
+}
+
+// View instruction: logs the ledger entries from oldest to newest and
+// returns the newest LEDGER_VIEW_MAX_ENTRIES of them, oldest first
+pub fn get_user_position_ledger(ctx: Context<GetUserPositionLedger>) -> Result<()> {
+    let ledger = &ctx.accounts.ledger;
+    let entries = ordered_entries(ledger);
+
+    msg!("Ledger owner: {}", ledger.owner);
+    for entry in entries.iter() {
+        msg!(
+            "op={} amount={} rate={} timestamp={} fee={}",
+            entry.operation,
+            entry.amount,
+            entry.exchange_rate_at_time,
+            entry.timestamp,
+            entry.fee_paid
+        );
+    }
+
+    let newest = &entries[entries.len().saturating_sub(LEDGER_VIEW_MAX_ENTRIES)..];
+    set_return_data(&encode_view_response(USER_POSITION_LEDGER_VIEW_VERSION, &newest.to_vec())?);
+
+    Ok(())
+}
+
+// Ledger entries from oldest to newest. Once the buffer has wrapped, the
+// oldest entry sits at next_index
+fn ordered_entries(ledger: &UserPositionLedger) -> Vec<LedgerEntry> {
+    let entry_count = ledger.entries.len();
+    let start = if entry_count < LEDGER_CAPACITY {
+        0
+    } else {
+        ledger.next_index as usize
+    };
+
+    (0..entry_count)
+        .map(|i| ledger.entries[(start + i) % entry_count])
+        .collect()
+}
+
+// Appends an entry, overwriting the oldest once the buffer is full.
+// Initializes the owner and bump on a freshly created ledger
+pub fn record_ledger_entry(
+    ledger: &mut UserPositionLedger,
+    owner: Pubkey,
+    bump: u8,
+    pool_state: &StablePoolState,
+    operation: u8,
+    amount: u64,
+    fee_paid: u64,
+) -> Result<()> {
+    if ledger.owner == Pubkey::default() {
+        ledger.owner = owner;
+        ledger.bump = bump;
+    }
+
+    // Share price after the operation, USDC per share scaled by 1e9
+    let exchange_rate_at_time = if pool_state.total_shares == 0 {
+        LEDGER_RATE_SCALE as u64
+    } else {
+        (pool_state.total_deposits as u128)
+            .checked_mul(LEDGER_RATE_SCALE)
+            .ok_or(StablecoinVaultError::MathOverflow)?
+            .checked_div(pool_state.total_shares as u128)
+            .ok_or(StablecoinVaultError::MathOverflow)? as u64
+    };
+
+    let entry = LedgerEntry {
+        operation,
+        amount,
+        exchange_rate_at_time,
+        timestamp: Clock::get()?.unix_timestamp,
+        fee_paid,
+    };
+    push_entry(ledger, entry);
+
+    Ok(())
+}
+
+fn push_entry(ledger: &mut UserPositionLedger, entry: LedgerEntry) {
+    if ledger.entries.len() < LEDGER_CAPACITY {
+        ledger.entries.push(entry);
+    } else {
+        ledger.entries[ledger.next_index as usize] = entry;
+        ledger.next_index = ((ledger.next_index as usize + 1) % LEDGER_CAPACITY) as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(amount: u64) -> LedgerEntry {
+        LedgerEntry {
+            operation: 0,
+            amount,
+            exchange_rate_at_time: LEDGER_RATE_SCALE as u64,
+            timestamp: amount as i64,
+            fee_paid: 0,
+        }
+    }
+
+    fn ledger_with(count: u64) -> UserPositionLedger {
+        let mut ledger = UserPositionLedger {
+            owner: Pubkey::default(),
+            entries: Vec::new(),
+            next_index: 0,
+            bump: 0,
+        };
+        for amount in 0..count {
+            push_entry(&mut ledger, entry(amount));
+        }
+        ledger
+    }
+
+    fn amounts(entries: &[LedgerEntry]) -> Vec<u64> {
+        entries.iter().map(|entry| entry.amount).collect()
+    }
+
+    #[test]
+    fn ledger_keeps_insertion_order_until_full() {
+        let ledger = ledger_with(3);
+        assert_eq!(amounts(&ordered_entries(&ledger)), vec![0, 1, 2]);
+        assert_eq!(ledger.next_index, 0);
+    }
+
+    #[test]
+    fn ledger_overwrites_oldest_once_full() {
+        let extra = 5;
+        let ledger = ledger_with(LEDGER_CAPACITY as u64 + extra);
+
+        assert_eq!(ledger.entries.len(), LEDGER_CAPACITY);
+        assert_eq!(ledger.next_index as u64, extra);
+        let expected: Vec<u64> = (extra..LEDGER_CAPACITY as u64 + extra).collect();
+        assert_eq!(amounts(&ordered_entries(&ledger)), expected);
+    }
+
+    #[test]
+    fn ledger_view_fits_in_return_data() {
+        let entries = vec![entry(0); LEDGER_VIEW_MAX_ENTRIES];
+        // ViewResponse adds a version byte and the payload's length prefix
+        let response_len = 1 + 4 + entries.try_to_vec().unwrap().len();
+        assert!(response_len <= anchor_lang::solana_program::program::MAX_RETURN_DATA);
+    }
+}
@@ -2,8 +2,10 @@ pub mod admin;
 pub mod deposits;
 pub mod withdrawals;
 pub mod rewards;
+pub mod ledger;
 
 pub use admin::*;
 pub use deposits::*;
 pub use withdrawals::*;
-pub use rewards::*;
\ No newline at end of file
+pub use rewards::*;
+pub use ledger::*;
\ No newline at end of file
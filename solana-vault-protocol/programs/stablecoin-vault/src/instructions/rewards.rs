@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount, Transfer};
-use crate::state::{StablecoinVaultConfig, UserStablePosition, StablePoolState, YieldSource};
+use crate::state::{StablecoinVaultConfig, UserStablePosition, StablePoolState, UserPositionLedger, LEDGER_ACCOUNT_SPACE, YieldSource, REWARD_INDEX_SCALE, REWARD_CLAIM_BIT, LEDGER_REWARD_CLAIM};
 use crate::errors::StablecoinVaultError;
+use vault_sol::state::{GlobalPause, VaultConfig};
+use vault_sol::utils::is_fee_exempt;
+use vault_math::apply_bps;
+use crate::instructions::ledger::record_ledger_entry;
 
 #[derive(Accounts)]
 pub struct ClaimStableRewards<'info> {
@@ -9,9 +13,26 @@ pub struct ClaimStableRewards<'info> {
         seeds = [b"stable_vault_config"],
         bump = config.bump,
         constraint = !config.paused @ StablecoinVaultError::VaultPaused,
+        constraint = config.pause_flags & (1 << REWARD_CLAIM_BIT) == 0 @ StablecoinVaultError::OperationPaused,
     )]
     pub config: Account<'info, StablecoinVaultConfig>,
 
+    // Protocol-wide fee exemption list
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ StablecoinVaultError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"user_stable_position", user.key().as_ref()],
@@ -26,17 +47,34 @@ pub struct ClaimStableRewards<'info> {
     )]
     pub pool_state: Account<'info, StablePoolState>,
 
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = LEDGER_ACCOUNT_SPACE,
+        seeds = [b"user_position_ledger", user.key().as_ref()],
+        bump
+    )]
+    pub ledger: Box<Account<'info, UserPositionLedger>>,
+
     #[account(mut)]
     pub user: Signer<'info>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_usdc_account.owner == user.key() @ StablecoinVaultError::InvalidTokenAccountOwner,
+        constraint = user_usdc_account.mint == config.reward_mint @ StablecoinVaultError::RewardMintMismatch,
+    )]
     pub user_usdc_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = vault_usdc_account.mint == config.reward_mint @ StablecoinVaultError::RewardMintMismatch,
+    )]
     pub vault_usdc_account: Account<'info, TokenAccount>,
 
     #[account(mut)]
     pub treasury: SystemAccount<'info>,
     
+    pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
 }
 
@@ -108,12 +146,14 @@ pub fn claim_stable_rewards(
     // Transfer rewards directly here
     require!(reward_amount > 0, StablecoinVaultError::InvalidAmount);
 
-    // Calculate platform fee
-    let fee_amount = (reward_amount as u128)
-        .checked_mul(config.platform_fee_bps as u128)
-        .ok_or(StablecoinVaultError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(StablecoinVaultError::MathOverflow)? as u64;
+    // Calculate platform fee; exempt accounts pay none
+    let platform_fee_bps = if is_fee_exempt(&ctx.accounts.vault_config, ctx.accounts.user.key) {
+        0
+    } else {
+        config.platform_fee_bps
+    };
+    let fee_amount = apply_bps(reward_amount, platform_fee_bps)
+        .ok_or(StablecoinVaultError::MathOverflow)?;
 
     let user_reward = reward_amount
         .checked_sub(fee_amount)
@@ -135,6 +175,17 @@ pub fn claim_stable_rewards(
     // Update last claim timestamp
     user_position.last_reward_claim = current_time;
 
+    let ledger_bump = *ctx.bumps.get("ledger").unwrap();
+    record_ledger_entry(
+        &mut ctx.accounts.ledger,
+        ctx.accounts.user.key(),
+        ledger_bump,
+        pool_state,
+        LEDGER_REWARD_CLAIM,
+        user_reward,
+        fee_amount,
+    )?;
+
     Ok(())
 }
 
@@ -148,9 +199,9 @@ fn calculate_lending_rewards(
     let base_reward = (shares as u128)
         .checked_mul(time_staked as u128)
         .ok_or(StablecoinVaultError::MathOverflow)?
-        .checked_mul(pool_state.stable_per_share as u128)
+        .checked_mul(pool_state.stable_per_share)
         .ok_or(StablecoinVaultError::MathOverflow)?
-        .checked_div(1_000_000_000)
+        .checked_div(REWARD_INDEX_SCALE)
         .ok_or(StablecoinVaultError::MathOverflow)? as u64;
 
     Ok(base_reward)
@@ -172,4 +223,50 @@ fn calculate_treasury_rewards(
         .ok_or(StablecoinVaultError::MathOverflow)? as u64;
 
     Ok(rewards)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vault_math::per_second_rate;
+
+    const USDC: u64 = 1_000_000;
+    const DAY: i64 = 24 * 60 * 60;
+
+    fn pool_with_rate(stable_per_share: u128) -> StablePoolState {
+        StablePoolState {
+            total_deposits: 0,
+            total_shares: 0,
+            apy_points: 0,
+            stable_per_share,
+            last_update: 0,
+            lending_ratio: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn daily_lending_claims_sum_to_the_annual_rate() {
+        // 1,000 USDC of shares at 5% APY claimed once a day for a year
+        let shares = 1_000 * USDC;
+        let pool_state = pool_with_rate(per_second_rate(500, REWARD_INDEX_SCALE).unwrap());
+
+        let claimed: u64 = (0..365)
+            .map(|_| calculate_lending_rewards(shares, DAY, &pool_state).unwrap())
+            .sum();
+
+        // Each claim rounds down by less than one unit
+        let annual = 50 * USDC;
+        assert!(claimed <= annual && annual - claimed <= 365);
+    }
+
+    #[test]
+    fn legacy_index_scale_under_distributes() {
+        // The pre-migration 1e9 index truncated 5% APY to a per-second rate
+        // of 1, paying out about 37% less
+        let shares = 1_000 * USDC;
+        let legacy_rate = per_second_rate(500, 1_000_000_000).unwrap();
+        let legacy_daily = (shares as u128 * DAY as u128 * legacy_rate / 1_000_000_000) as u64;
+
+        assert_eq!(legacy_daily * 365, 31_536_000);
+    }
+}
@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount, Transfer};
-use crate::state::{StablecoinVaultConfig, UserStablePosition, StablePoolState};
+use crate::state::{StablecoinVaultConfig, UserStablePosition, StablePoolState, UserPositionLedger, LEDGER_ACCOUNT_SPACE, FeeModel, WITHDRAWAL_BIT, LEDGER_WITHDRAWAL};
 use crate::errors::StablecoinVaultError;
+use vault_sol::state::{GlobalPause, VaultConfig};
+use vault_sol::utils::is_fee_exempt;
+use crate::math::{div_ceil, div_floor};
+use crate::instructions::ledger::record_ledger_entry;
 
 #[derive(Accounts)]
 pub struct WithdrawStable<'info> {
@@ -9,9 +13,26 @@ pub struct WithdrawStable<'info> {
         seeds = [b"stable_vault_config"],
         bump = config.bump,
         constraint = !config.paused @ StablecoinVaultError::VaultPaused,
+        constraint = config.pause_flags & (1 << WITHDRAWAL_BIT) == 0 @ StablecoinVaultError::OperationPaused,
     )]
     pub config: Account<'info, StablecoinVaultConfig>,
 
+    // Protocol-wide fee exemption list
+    #[account(
+        seeds = [b"vault_sol_config"],
+        seeds::program = vault_sol::ID,
+        bump = vault_config.bump,
+    )]
+    pub vault_config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        seeds::program = vault_sol::ID,
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ StablecoinVaultError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"user_stable_position", user.key().as_ref()],
@@ -27,17 +48,34 @@ pub struct WithdrawStable<'info> {
     )]
     pub pool_state: Account<'info, StablePoolState>,
 
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = LEDGER_ACCOUNT_SPACE,
+        seeds = [b"user_position_ledger", user.key().as_ref()],
+        bump
+    )]
+    pub ledger: Box<Account<'info, UserPositionLedger>>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = user_usdc_account.owner == user.key() @ StablecoinVaultError::InvalidTokenAccountOwner,
+        constraint = user_usdc_account.mint == vault_usdc_account.mint @ StablecoinVaultError::InvalidTokenAccount,
+    )]
     pub user_usdc_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        address = config.vault_usdc_account @ StablecoinVaultError::InvalidTokenAccount,
+    )]
     pub vault_usdc_account: Account<'info, TokenAccount>,
 
     #[account(mut)]
     pub treasury: SystemAccount<'info>,
 
+    pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
 }
 
@@ -49,26 +87,45 @@ pub fn withdraw_stable(
     let user_position = &mut ctx.accounts.user_position;
     let pool_state = &mut ctx.accounts.pool_state;
 
+    // Value the position at the pool share price so donations to the
+    // reserve accrue to existing holders; rounds down
+    let position_value = div_floor(
+        (user_position.shares as u128)
+            .checked_mul(pool_state.total_deposits as u128)
+            .ok_or(StablecoinVaultError::MathOverflow)?,
+        pool_state.total_shares as u128,
+    )? as u64;
+
     // Validate withdrawal amount
     require!(amount > 0, StablecoinVaultError::InvalidAmount);
     require!(
-        amount <= user_position.stablecoin_amount,
+        amount <= position_value,
         StablecoinVaultError::InsufficientBalance
     );
 
-    // Calculate shares to burn
-    let shares_to_burn = (amount as u128)
-        .checked_mul(user_position.shares as u128)
+    // Calculate shares to burn, rounding up so the withdrawer rather than
+    // the pool absorbs the remainder. Never exceeds the position's shares
+    // since amount <= position_value
+    let shares_to_burn = div_ceil(
+        (amount as u128)
+            .checked_mul(pool_state.total_shares as u128)
+            .ok_or(StablecoinVaultError::MathOverflow)?,
+        pool_state.total_deposits as u128,
+    )? as u64;
+
+    // Principal attributable to this withdrawal, pro rata to position value
+    let cost_basis = (amount as u128)
+        .checked_mul(user_position.stablecoin_amount as u128)
         .ok_or(StablecoinVaultError::MathOverflow)?
-        .checked_div(user_position.stablecoin_amount as u128)
+        .checked_div(position_value as u128)
         .ok_or(StablecoinVaultError::MathOverflow)? as u64;
 
-    // Calculate fees
-    let fee_amount = (amount as u128)
-        .checked_mul(config.platform_fee_bps as u128)
-        .ok_or(StablecoinVaultError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(StablecoinVaultError::MathOverflow)? as u64;
+    // Calculate fees; exempt accounts pay none
+    let fee_amount = if is_fee_exempt(&ctx.accounts.vault_config, ctx.accounts.user.key) {
+        0
+    } else {
+        calculate_withdrawal_fee(config.fee_model, amount, cost_basis)?
+    };
 
     let withdrawal_amount = amount
         .checked_sub(fee_amount)
@@ -87,10 +144,9 @@ pub fn withdraw_stable(
         withdrawal_amount,
     )?;
 
-    // Update user position
+    // Update user position; principal tracking excludes share price gains
     user_position.stablecoin_amount = user_position.stablecoin_amount
-        .checked_sub(amount)
-        .ok_or(StablecoinVaultError::MathOverflow)?;
+        .saturating_sub(cost_basis);
     user_position.shares = user_position.shares
         .checked_sub(shares_to_burn)
         .ok_or(StablecoinVaultError::MathOverflow)?;
@@ -104,5 +160,38 @@ pub fn withdraw_stable(
         .ok_or(StablecoinVaultError::MathOverflow)?;
     pool_state.last_update = Clock::get()?.unix_timestamp;
 
+    let ledger_bump = *ctx.bumps.get("ledger").unwrap();
+    record_ledger_entry(
+        &mut ctx.accounts.ledger,
+        ctx.accounts.user.key(),
+        ledger_bump,
+        pool_state,
+        LEDGER_WITHDRAWAL,
+        amount,
+        fee_amount,
+    )?;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+// Helper function to calculate the withdrawal fee under the configured model
+fn calculate_withdrawal_fee(
+    fee_model: FeeModel,
+    amount: u64,
+    cost_basis: u64,
+) -> Result<u64> {
+    let (fee_base, fee_bps) = match fee_model {
+        FeeModel::FlatWithdrawal { fee_bps } => (amount, fee_bps),
+        FeeModel::ProfitOnly { fee_bps } => (amount.saturating_sub(cost_basis), fee_bps),
+    };
+
+    // Fees round up
+    let fee = div_ceil(
+        (fee_base as u128)
+            .checked_mul(fee_bps as u128)
+            .ok_or(StablecoinVaultError::MathOverflow)?,
+        10000,
+    )? as u64;
+
+    Ok(fee)
+}
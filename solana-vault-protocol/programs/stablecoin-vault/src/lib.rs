@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 
 pub mod errors;
+pub mod math;
 pub mod state;
 pub mod instructions;
+pub mod view;
 
 use instructions::*;
 use state::*;
@@ -29,6 +31,13 @@ pub mod stablecoin_vault {
         instructions::deposits::deposit_stable(ctx, amount)
     }
 
+    pub fn donate_to_reserve(
+        ctx: Context<DonateToReserve>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::deposits::donate_to_reserve(ctx, amount)
+    }
+
     pub fn withdraw_stable(
         ctx: Context<WithdrawStable>,
         amount: u64,
@@ -43,11 +52,23 @@ pub mod stablecoin_vault {
         instructions::rewards::claim_stable_rewards(ctx, source)
     }
 
-    pub fn update_lending_ratio(
-        ctx: Context<UpdateLendingRatio>,
+    pub fn get_user_position_ledger(ctx: Context<GetUserPositionLedger>) -> Result<()> {
+        instructions::ledger::get_user_position_ledger(ctx)
+    }
+
+    pub fn propose_lending_ratio_change(
+        ctx: Context<ProposeLendingRatioChange>,
         new_ratio: u16,
     ) -> Result<()> {
-        instructions::admin::update_lending_ratio(ctx, new_ratio)
+        instructions::admin::propose_lending_ratio_change(ctx, new_ratio)
+    }
+
+    pub fn execute_lending_ratio_change(ctx: Context<ExecuteLendingRatioChange>) -> Result<()> {
+        instructions::admin::execute_lending_ratio_change(ctx)
+    }
+
+    pub fn cancel_lending_ratio_proposal(ctx: Context<CancelLendingRatioProposal>) -> Result<()> {
+        instructions::admin::cancel_lending_ratio_proposal(ctx)
     }
 
     pub fn toggle_lending(
@@ -57,6 +78,28 @@ pub mod stablecoin_vault {
         instructions::admin::toggle_lending(ctx, enabled)
     }
 
+    pub fn set_lending_reward_rate(
+        ctx: Context<SetLendingRewardRate>,
+        apy_points: u16,
+    ) -> Result<()> {
+        instructions::admin::set_lending_reward_rate(ctx, apy_points)
+    }
+
+    pub fn set_fee_model(
+        ctx: Context<SetFeeModel>,
+        fee_model: FeeModel,
+    ) -> Result<()> {
+        instructions::admin::set_fee_model(ctx, fee_model)
+    }
+
+    pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+        instructions::admin::migrate_pool_state(ctx)
+    }
+
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, flags: u8) -> Result<()> {
+        instructions::admin::set_pause_flags(ctx, flags)
+    }
+
     pub fn pause_vault(ctx: Context<PauseVault>) -> Result<()> {
         instructions::admin::pause_vault(ctx)
     }
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use crate::errors::StablecoinVaultError;
+
+// Share and fee math rounds in the protocol's favor: amounts paid out or
+// minted to users round down, amounts taken from users round up
+
+pub fn div_floor(numerator: u128, denominator: u128) -> Result<u128> {
+    Ok(numerator
+        .checked_div(denominator)
+        .ok_or(StablecoinVaultError::MathOverflow)?)
+}
+
+pub fn div_ceil(numerator: u128, denominator: u128) -> Result<u128> {
+    require!(denominator > 0, StablecoinVaultError::MathOverflow);
+    Ok(numerator.div_ceil(denominator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floor_rounds_down_and_ceil_rounds_up() {
+        assert_eq!(div_floor(10, 3).unwrap(), 3);
+        assert_eq!(div_ceil(10, 3).unwrap(), 4);
+        assert_eq!(div_floor(9, 3).unwrap(), 3);
+        assert_eq!(div_ceil(9, 3).unwrap(), 3);
+    }
+
+    #[test]
+    fn share_minting_and_burning_favor_the_pool() {
+        // Depositing 100 into a pool of 300 deposits backed by 200 shares
+        let minted = div_floor(100 * 200, 300).unwrap();
+        assert_eq!(minted, 66);
+
+        // Withdrawing those 100 again burns more shares than were minted
+        let burned = div_ceil(100 * 200, 300).unwrap();
+        assert_eq!(burned, 67);
+    }
+
+    #[test]
+    fn zero_denominator_is_an_error() {
+        assert!(div_floor(1, 0).is_err());
+        assert!(div_ceil(1, 0).is_err());
+    }
+}
@@ -1,5 +1,15 @@
 use anchor_lang::prelude::*;
 
+// Default timelock on lending ratio changes (~1 day at 400ms slots)
+pub const DEFAULT_GOVERNANCE_DELAY_SLOTS: u64 = 216_000;
+
+// Bit positions in pause_flags
+pub const DEPOSIT_BIT: u8 = 0;
+pub const WITHDRAWAL_BIT: u8 = 1;
+pub const REWARD_CLAIM_BIT: u8 = 5;
+// Every defined bit; set_pause_flags rejects the rest
+pub const PAUSE_FLAGS_MASK: u8 = (1 << DEPOSIT_BIT) | (1 << WITHDRAWAL_BIT) | (1 << REWARD_CLAIM_BIT);
+
 #[account]
 pub struct StablecoinVaultConfig {
     pub authority: Pubkey,
@@ -7,7 +17,13 @@ pub struct StablecoinVaultConfig {
     pub platform_fee_bps: u16,
     pub min_deposit_amount: u64,
     pub lending_enabled: bool,  // Whether vault can lend to protocols
+    pub fee_model: FeeModel,  // Fee applied on withdraw_stable
+    pub reward_mint: Pubkey,
+    pub governance_delay_slots: u64,  // Timelock on lending ratio changes
+    pub usdc_mint: Pubkey,
+    pub vault_usdc_account: Pubkey,  // Reserve holding every deposit
     pub paused: bool,
+    pub pause_flags: u8,  // Per-operation pause bits, see *_BIT
     pub bump: u8,
 }
 
@@ -21,17 +37,74 @@ pub struct UserStablePosition {
     pub bump: u8,
 }
 
+// Pending lending ratio change, executable once the timelock elapses
+#[account]
+pub struct LendingRatioProposal {
+    pub proposed_ratio: u16,
+    pub executable_after_slot: u64,
+    pub bump: u8,
+}
+
+// Ledger operation codes
+pub const LEDGER_DEPOSIT: u8 = 0;
+pub const LEDGER_WITHDRAWAL: u8 = 1;
+pub const LEDGER_REWARD_CLAIM: u8 = 2;
+
+pub const LEDGER_CAPACITY: usize = 50;
+// Discriminator, owner, vec prefix, full buffer of 33-byte entries,
+// next_index and bump
+pub const LEDGER_ACCOUNT_SPACE: usize = 8 + 32 + 4 + LEDGER_CAPACITY * 33 + 1 + 1;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct LedgerEntry {
+    pub operation: u8,               // See LEDGER_* codes
+    pub amount: u64,
+    pub exchange_rate_at_time: u64,  // USDC per share, multiplied by 1e9
+    pub timestamp: i64,
+    pub fee_paid: u64,
+}
+
+// Ring buffer of a user's most recent position changes
+#[account]
+pub struct UserPositionLedger {
+    pub owner: Pubkey,
+    pub entries: Vec<LedgerEntry>,  // At most LEDGER_CAPACITY entries
+    pub next_index: u8,  // Slot the next entry is written to once full
+    pub bump: u8,
+}
+
+// Scale of the per-share reward index
+pub const REWARD_INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+
 #[account]
 pub struct StablePoolState {
     pub total_deposits: u64,
     pub total_shares: u64,
     pub apy_points: u16,  // Current APY in basis points
-    pub stable_per_share: u64,  // Multiplied by 1e9
+    pub stable_per_share: u128,  // Lending reward per share per second, multiplied by 1e18
     pub last_update: i64,
     pub lending_ratio: u16,  // Max ratio that can be lent out (bps)
     pub bump: u8,
 }
 
+// Pre-migration StablePoolState layout with a u64 index scaled by 1e9
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct StablePoolStateV1 {
+    pub total_deposits: u64,
+    pub total_shares: u64,
+    pub apy_points: u16,
+    pub stable_per_share: u64,
+    pub last_update: i64,
+    pub lending_ratio: u16,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum FeeModel {
+    FlatWithdrawal { fee_bps: u16 },  // Fee on the full withdrawn amount
+    ProfitOnly { fee_bps: u16 },      // Fee on gains above principal only
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum YieldSource {
     Lending,     // External lending protocols
@@ -0,0 +1,4 @@
+// Payload versions for this program's views, wrapped in a
+// vault_math::view::ViewResponse
+// GetUserPositionLedger: Vec<LedgerEntry>, oldest first
+pub const USER_POSITION_LEDGER_VIEW_VERSION: u8 = 1;
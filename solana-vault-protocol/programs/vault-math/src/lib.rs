@@ -0,0 +1,62 @@
+// Shared by every program in the protocol. Helpers return None on overflow
+// so each program can map it onto its own MathOverflow error.
+
+pub mod view;
+
+// Applies a basis-point rate to an amount, rounding down
+pub fn apply_bps(amount: u64, bps: u16) -> Option<u64> {
+    let scaled = (amount as u128)
+        .checked_mul(bps as u128)?
+        .checked_div(10000)?;
+
+    u64::try_from(scaled).ok()
+}
+
+// Seconds in a 365-day year
+pub const SECONDS_PER_YEAR: u128 = 365 * 24 * 60 * 60;
+
+// Per-second reward per unit staked for an APY in basis points, multiplied
+// by `scale`; rounds down
+pub fn per_second_rate(apy_bps: u16, scale: u128) -> Option<u128> {
+    (apy_bps as u128)
+        .checked_mul(scale)?
+        .checked_div(SECONDS_PER_YEAR * 10000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_bps_rounds_down() {
+        assert_eq!(apply_bps(10_000, 50), Some(50));
+        assert_eq!(apply_bps(199, 50), Some(0));
+        assert_eq!(apply_bps(0, 10000), Some(0));
+    }
+
+    #[test]
+    fn apply_bps_holds_large_amounts() {
+        assert_eq!(apply_bps(u64::MAX, 10000), Some(u64::MAX));
+        assert_eq!(apply_bps(u64::MAX, 5000), Some(u64::MAX / 2));
+        assert_eq!(apply_bps(u64::MAX, 1), Some(u64::MAX / 10000));
+    }
+
+    #[test]
+    fn apply_bps_rejects_results_past_u64() {
+        assert_eq!(apply_bps(u64::MAX, 10001), None);
+        assert_eq!(apply_bps(u64::MAX, u16::MAX), None);
+    }
+
+    #[test]
+    fn per_second_rate_keeps_small_rates_at_high_scale() {
+        // 5% APY is about 1.585e-9 per second
+        assert_eq!(per_second_rate(500, 1_000_000_000), Some(1));
+        assert_eq!(per_second_rate(500, 1_000_000_000_000_000_000), Some(1_585_489_599));
+        assert_eq!(per_second_rate(0, 1_000_000_000_000_000_000), Some(0));
+    }
+
+    #[test]
+    fn per_second_rate_rejects_overflowing_scales() {
+        assert_eq!(per_second_rate(2, u128::MAX), None);
+    }
+}
@@ -0,0 +1,55 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+// Envelope written as return data by every view instruction in the
+// protocol. `version` identifies the payload layout of the view that wrote
+// it and is bumped whenever that layout changes; each program keeps its
+// versions as *_VIEW_VERSION consts in its own view module
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ViewResponse {
+    pub version: u8,
+    pub payload: Vec<u8>,
+}
+
+// Wraps a borsh-encoded payload in a ViewResponse, ready for set_return_data
+pub fn encode_view_response<T: BorshSerialize>(version: u8, payload: &T) -> std::io::Result<Vec<u8>> {
+    let response = ViewResponse {
+        version,
+        payload: payload.try_to_vec()?,
+    };
+    response.try_to_vec()
+}
+
+// Decodes a ViewResponse payload; None if the data is malformed or written
+// by any version other than the one the caller knows how to parse
+pub fn read_view_response<T: BorshDeserialize>(data: &[u8], expected_version: u8) -> Option<T> {
+    let response = ViewResponse::try_from_slice(data).ok()?;
+    if response.version != expected_version {
+        return None;
+    }
+
+    T::try_from_slice(&response.payload).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_response_round_trips() {
+        let data = encode_view_response(1, &(42u64, 7u16)).unwrap();
+        assert_eq!(read_view_response::<(u64, u16)>(&data, 1), Some((42, 7)));
+    }
+
+    #[test]
+    fn view_response_rejects_other_versions() {
+        let data = encode_view_response(2, &42u64).unwrap();
+        assert_eq!(read_view_response::<u64>(&data, 1), None);
+    }
+
+    #[test]
+    fn view_response_prefixes_version_and_length() {
+        let data = encode_view_response(3, &42u64).unwrap();
+        assert_eq!(&data[..5], &[3, 8, 0, 0, 0]);
+        assert_eq!(data.len(), 1 + 4 + 8);
+    }
+}
@@ -28,5 +28,58 @@ pub enum VaultSolError {
 
     #[msg("Rewards pool depleted")]
     InsufficientRewards,
-}
 
+    #[msg("Gasless mode is not enabled for this user")]
+    GaslessModeDisabled,
+
+    #[msg("Transaction fee exceeds maximum subsidy")]
+    SubsidyTooLarge,
+
+    #[msg("Stake below minimum for gas subsidy")]
+    BelowSubsidyThreshold,
+
+    #[msg("Invalid user tier")]
+    InvalidTier,
+
+    #[msg("Deposit exceeds cap for user tier")]
+    TierDepositCapExceeded,
+
+    #[msg("Validator rewards already processed for this epoch")]
+    EpochAlreadyProcessed,
+
+    #[msg("Invalid validator stake account")]
+    InvalidStakeAccount,
+
+    #[msg("Mint authority is not the vault")]
+    InvalidMintAuthority,
+
+    #[msg("Operation is paused")]
+    OperationPaused,
+
+    #[msg("Invalid custom reward schedule")]
+    InvalidCustomRewardSchedule,
+
+    #[msg("Debit would leave the account below rent exemption")]
+    RentExemptionViolation,
+
+    #[msg("Treasury cannot be the default pubkey")]
+    InvalidTreasury,
+
+    #[msg("Unsupported view response version")]
+    UnsupportedViewVersion,
+
+    #[msg("Fee exemption list is full")]
+    FeeExemptListFull,
+
+    #[msg("Protocol is globally paused")]
+    GloballyPaused,
+
+    #[msg("Reward claim cooldown has not elapsed")]
+    ClaimCooldownActive,
+
+    #[msg("Existing custom reward schedule is still accruing or holds unclaimed bonus")]
+    CustomRewardScheduleActive,
+
+    #[msg("Pause flags set an undefined bit")]
+    InvalidPauseFlags,
+}
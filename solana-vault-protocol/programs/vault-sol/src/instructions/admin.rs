@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
-use crate::state::{VaultConfig, RewardsPool, LSTProvider};
+use anchor_lang::solana_program::{program::invoke, program_option::COption, stake::{self, state::StakeState}, system_instruction};
+use anchor_spl::token::Mint;
+use crate::state::{VaultConfig, GlobalPause, ValidatorStakeRecord, RewardsPool, SolVault, ProtocolStats, LSTProvider, UserTier, CustomUserRewardSchedule, LargeDepositorBoost, MAX_PLATFORM_FEE_BPS, MAX_FEE_EXEMPT_ACCOUNTS, EXCHANGE_RATE_SCALE, PAUSE_FLAGS_MASK};
 use crate::errors::VaultSolError;
+use crate::instructions::rewards::accrued_custom_bonus;
 
 #[derive(Accounts)]
 pub struct InitializeVault<'info> {
@@ -21,6 +24,31 @@ pub struct InitializeVault<'info> {
         bump
     )]
     pub rewards_pool: Account<'info, RewardsPool>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<SolVault>(),
+        seeds = [b"sol_vault"],
+        bump
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<ProtocolStats>(),
+        seeds = [b"protocol_stats"],
+        bump
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
+    // Staking mints vSOL with the config as authority, so refuse to start
+    // against a mint the vault cannot mint from
+    #[account(
+        constraint = vsol_mint.mint_authority == COption::Some(config.key()) @ VaultSolError::InvalidMintAuthority
+    )]
+    pub vsol_mint: Account<'info, Mint>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
@@ -68,6 +96,20 @@ pub struct AddRewards<'info> {
     
     #[account(mut)]
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+    
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
@@ -83,6 +125,62 @@ pub struct PauseVault<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct InitializeGlobalPause<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<GlobalPause>(),
+        seeds = [b"global_pause"],
+        bump
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"global_pause"],
+        bump = global_pause.bump,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGlobalPause<'info> {
+    #[account(
+        mut,
+        has_one = guardian,
+        seeds = [b"global_pause"],
+        bump = global_pause.bump,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
+    pub guardian: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct UnpauseVault<'info> {
     #[account(
@@ -96,13 +194,250 @@ pub struct UnpauseVault<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct AddGasSubsidyFunds<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"gas_subsidy_reserve"],
+        bump,
+    )]
+    pub gas_subsidy_reserve: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGasSubsidyParams<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user_key: Pubkey)]
+pub struct SetUserTier<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<UserTier>(),
+        seeds = [b"user_tier", user_key.as_ref()],
+        bump
+    )]
+    pub user_tier: Account<'info, UserTier>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTierDepositCaps<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxPoolFeeBps<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDustThreshold<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMinClaimInterval<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeExemption<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardBoostForLargeDepositors<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct SetCustomRewardScheduleForUser<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"rewards_pool"],
+        bump = rewards_pool.bump,
+    )]
+    pub rewards_pool: Account<'info, RewardsPool>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<CustomUserRewardSchedule>(),
+        seeds = [b"custom_reward_schedule", user.as_ref()],
+        bump
+    )]
+    pub custom_reward_schedule: Account<'info, CustomUserRewardSchedule>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveCustomRewardSchedule<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"custom_reward_schedule", custom_reward_schedule.user.as_ref()],
+        bump = custom_reward_schedule.bump,
+    )]
+    pub custom_reward_schedule: Account<'info, CustomUserRewardSchedule>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessValidatorRewards<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    pub authority: Signer<'info>,
+    // (stake account, ValidatorStakeRecord) pairs are passed as remaining accounts
+}
+
+#[derive(Accounts)]
+pub struct RegisterValidatorStakeAccount<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<ValidatorStakeRecord>(),
+        seeds = [b"validator_stake", stake_account.key().as_ref()],
+        bump
+    )]
+    pub validator_stake_record: Account<'info, ValidatorStakeRecord>,
+
+    /// CHECK: deserialized as a stake account in the handler
+    #[account(owner = stake::program::ID @ VaultSolError::InvalidStakeAccount)]
+    pub stake_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 pub fn initialize_vault(
     ctx: Context<InitializeVault>,
     platform_fee_bps: u16,
     min_stake: u64,
 ) -> Result<()> {
-    require!(platform_fee_bps <= 10000, VaultSolError::InvalidFee);
+    require!(platform_fee_bps <= MAX_PLATFORM_FEE_BPS, VaultSolError::InvalidFee);
     require!(min_stake > 0, VaultSolError::InvalidAmount);
+    require!(
+        ctx.accounts.treasury.key() != Pubkey::default(),
+        VaultSolError::InvalidTreasury
+    );
 
     // Initialize config
     let config = &mut ctx.accounts.config;
@@ -115,8 +450,25 @@ pub fn initialize_vault(
     config.total_staked = 0;
     config.stakers_count = 0;
     config.paused = false;
+    config.pause_flags = 0;
     config.active_provider = LSTProvider::None;  // Initialize with no LST provider
     config.bump = config_bump;
+    config.gas_subsidy_reserve = Pubkey::default();  // Set on first AddGasSubsidyFunds
+    config.max_subsidy_per_tx = 0;
+    config.min_stake_for_gas_subsidy = 0;
+    config.tier_deposit_caps = [u64::MAX; 4];  // Uncapped until SetTierDepositCaps
+    config.exchange_rate_numerator = EXCHANGE_RATE_SCALE;  // 1 SOL per vSOL
+    config.last_processed_epoch = 0;
+    config.dust_threshold = 1000;
+    config.large_depositor_boost = LargeDepositorBoost {
+        threshold_amount: u64::MAX,  // Disabled until configured
+        boost_bps: 0,
+    };
+    config.fee_exempt = [Pubkey::default(); MAX_FEE_EXEMPT_ACCOUNTS];
+    config.fee_exempt_count = 0;
+    config.min_claim_interval_seconds = 0;
+    config.max_pool_fee_bps = MAX_PLATFORM_FEE_BPS;
+    config.vsol_backing = 0;
 
     // Initialize rewards pool
     let rewards_pool = &mut ctx.accounts.rewards_pool;
@@ -128,6 +480,19 @@ pub fn initialize_vault(
     rewards_pool.distributed_rewards = 0;
     rewards_pool.bump = pool_bump;
 
+    ctx.accounts.sol_vault.bump = *ctx.bumps.get("sol_vault").unwrap();
+
+    // Initialize protocol stats so stake, withdraw and claim can always
+    // record into them
+    let protocol_stats = &mut ctx.accounts.protocol_stats;
+    protocol_stats.total_deposits_all_time = 0;
+    protocol_stats.total_withdrawals_all_time = 0;
+    protocol_stats.total_rewards_distributed_all_time = 0;
+    protocol_stats.unique_users = 0;
+    protocol_stats.peak_tvl = 0;
+    protocol_stats.last_updated_slot = Clock::get()?.slot;
+    protocol_stats.bump = *ctx.bumps.get("protocol_stats").unwrap();
+
     Ok(())
 }
 
@@ -157,29 +522,22 @@ pub fn add_rewards(
     // Now we can safely mutably borrow rewards_pool
     let rewards_pool = &mut ctx.accounts.rewards_pool;
     
-    // Initialize rewards pool if it's the first time
-    if rewards_pool.total_rewards == 0 {
-        rewards_pool.apy_points = 500; // Default 5% APY
-        rewards_pool.distributed_rewards = 0;
-        rewards_pool.bump = *ctx.bumps.get("rewards_pool").unwrap();
-    }
-    
     // Transfer SOL to rewards pool
     require!(
         ctx.accounts.authority.lamports() >= amount,
         VaultSolError::InsufficientBalance
     );
 
-    // Transfer lamports from authority to rewards pool
-    **authority_info.try_borrow_mut_lamports()? = authority_info
-        .lamports()
-        .checked_sub(amount)
-        .ok_or(VaultSolError::MathOverflow)?;
-
-    **rewards_pool_info.try_borrow_mut_lamports()? = rewards_pool_info
-        .lamports()
-        .checked_add(amount)
-        .ok_or(VaultSolError::MathOverflow)?;
+    // The authority is a system account, so the system program moves the
+    // lamports on its signature
+    invoke(
+        &system_instruction::transfer(authority_info.key, rewards_pool_info.key, amount),
+        &[
+            authority_info,
+            rewards_pool_info,
+            ctx.accounts.system_program.to_account_info(),
+        ],
+    )?;
 
     rewards_pool.total_rewards = rewards_pool.total_rewards
         .checked_add(amount)
@@ -190,6 +548,14 @@ pub fn add_rewards(
     Ok(())
 }
 
+pub fn set_pause_flags(ctx: Context<SetPauseFlags>, flags: u8) -> Result<()> {
+    require!(flags & !PAUSE_FLAGS_MASK == 0, VaultSolError::InvalidPauseFlags);
+
+    let config = &mut ctx.accounts.config;
+    config.pause_flags = flags;
+    Ok(())
+}
+
 pub fn pause_vault(ctx: Context<PauseVault>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = true;
@@ -200,4 +566,367 @@ pub fn unpause_vault(ctx: Context<UnpauseVault>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = false;
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn initialize_global_pause(
+    ctx: Context<InitializeGlobalPause>,
+    guardian: Pubkey,
+) -> Result<()> {
+    let global_pause = &mut ctx.accounts.global_pause;
+    global_pause.guardian = guardian;
+    global_pause.paused = false;
+    global_pause.bump = *ctx.bumps.get("global_pause").unwrap();
+    Ok(())
+}
+
+pub fn set_guardian(ctx: Context<SetGuardian>, new_guardian: Pubkey) -> Result<()> {
+    let global_pause = &mut ctx.accounts.global_pause;
+    global_pause.guardian = new_guardian;
+    Ok(())
+}
+
+// Halts or resumes every program in the protocol at once
+pub fn set_global_pause(ctx: Context<SetGlobalPause>, paused: bool) -> Result<()> {
+    let global_pause = &mut ctx.accounts.global_pause;
+    global_pause.paused = paused;
+    Ok(())
+}
+
+pub fn add_gas_subsidy_funds(
+    ctx: Context<AddGasSubsidyFunds>,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, VaultSolError::InvalidAmount);
+
+    // Transfer SOL from authority to the gas subsidy reserve
+    anchor_lang::solana_program::program::invoke(
+        &anchor_lang::solana_program::system_instruction::transfer(
+            ctx.accounts.authority.key,
+            ctx.accounts.gas_subsidy_reserve.key,
+            amount,
+        ),
+        &[
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.gas_subsidy_reserve.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+    )?;
+
+    let config = &mut ctx.accounts.config;
+    config.gas_subsidy_reserve = ctx.accounts.gas_subsidy_reserve.key();
+
+    Ok(())
+}
+
+pub fn update_gas_subsidy_params(
+    ctx: Context<UpdateGasSubsidyParams>,
+    max_subsidy_per_tx: u64,
+    min_stake_for_gas_subsidy: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.max_subsidy_per_tx = max_subsidy_per_tx;
+    config.min_stake_for_gas_subsidy = min_stake_for_gas_subsidy;
+    Ok(())
+}
+
+pub fn set_user_tier(
+    ctx: Context<SetUserTier>,
+    user_key: Pubkey,
+    tier: u8,
+) -> Result<()> {
+    require!((tier as usize) < 4, VaultSolError::InvalidTier);
+
+    let user_tier = &mut ctx.accounts.user_tier;
+    user_tier.user_key = user_key;
+    user_tier.tier = tier;
+    user_tier.verified_at_slot = Clock::get()?.slot;
+    user_tier.bump = *ctx.bumps.get("user_tier").unwrap();
+
+    Ok(())
+}
+
+pub fn set_tier_deposit_caps(
+    ctx: Context<SetTierDepositCaps>,
+    caps: [u64; 4],
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.tier_deposit_caps = caps;
+    Ok(())
+}
+
+// Programs only read the limit when fees are set, so lowering it does not
+// touch fees already configured in the other programs
+pub fn set_max_pool_fee_bps(
+    ctx: Context<SetMaxPoolFeeBps>,
+    max_pool_fee_bps: u16,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    require!(
+        max_pool_fee_bps <= MAX_PLATFORM_FEE_BPS && config.platform_fee_bps <= max_pool_fee_bps,
+        VaultSolError::InvalidFee
+    );
+
+    config.max_pool_fee_bps = max_pool_fee_bps;
+
+    Ok(())
+}
+
+pub fn set_dust_threshold(
+    ctx: Context<SetDustThreshold>,
+    dust_threshold: u64,
+) -> Result<()> {
+    require!(
+        (1..=1_000_000).contains(&dust_threshold),
+        VaultSolError::InvalidAmount
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.dust_threshold = dust_threshold;
+
+    Ok(())
+}
+
+pub fn set_min_claim_interval(
+    ctx: Context<SetMinClaimInterval>,
+    min_claim_interval_seconds: i64,
+) -> Result<()> {
+    require!(min_claim_interval_seconds >= 0, VaultSolError::InvalidAmount);
+
+    let config = &mut ctx.accounts.config;
+    config.min_claim_interval_seconds = min_claim_interval_seconds;
+
+    Ok(())
+}
+
+// Adds `account` to or removes it from the platform fee exemption list
+pub fn set_fee_exemption(
+    ctx: Context<SetFeeExemption>,
+    account: Pubkey,
+    exempt: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let count = config.fee_exempt_count as usize;
+    let position = config.fee_exempt[..count]
+        .iter()
+        .position(|exempt_account| *exempt_account == account);
+
+    match (position, exempt) {
+        (None, true) => {
+            require!(count < MAX_FEE_EXEMPT_ACCOUNTS, VaultSolError::FeeExemptListFull);
+            config.fee_exempt[count] = account;
+            config.fee_exempt_count += 1;
+        },
+        (Some(index), false) => {
+            // Keep the list packed by moving the last entry into the gap
+            config.fee_exempt[index] = config.fee_exempt[count - 1];
+            config.fee_exempt[count - 1] = Pubkey::default();
+            config.fee_exempt_count -= 1;
+        },
+        _ => {},
+    }
+
+    Ok(())
+}
+
+pub fn set_reward_boost_for_large_depositors(
+    ctx: Context<SetRewardBoostForLargeDepositors>,
+    threshold_amount: u64,
+    boost_bps: u16,
+) -> Result<()> {
+    require!(boost_bps <= 10000, VaultSolError::InvalidAmount);
+
+    let config = &mut ctx.accounts.config;
+    config.large_depositor_boost = LargeDepositorBoost {
+        threshold_amount,
+        boost_bps,
+    };
+    Ok(())
+}
+
+pub fn set_custom_reward_schedule_for_user(
+    ctx: Context<SetCustomRewardScheduleForUser>,
+    user: Pubkey,
+    bonus_rate_per_second: u64,
+    start_timestamp: i64,
+    end_timestamp: i64,
+) -> Result<()> {
+    require!(
+        bonus_rate_per_second > 0 && end_timestamp > start_timestamp,
+        VaultSolError::InvalidCustomRewardSchedule
+    );
+
+    // An existing grant can only be replaced once it has ended and been
+    // fully claimed, and the new window must not overlap it; otherwise
+    // resetting claimed_bonus would pay the same seconds twice
+    let custom_reward_schedule = &mut ctx.accounts.custom_reward_schedule;
+    if custom_reward_schedule.user != Pubkey::default() {
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            custom_reward_schedule.end_timestamp <= current_time &&
+            accrued_custom_bonus(custom_reward_schedule, current_time)? == 0 &&
+            start_timestamp >= custom_reward_schedule.end_timestamp,
+            VaultSolError::CustomRewardScheduleActive
+        );
+    }
+
+    custom_reward_schedule.user = user;
+    custom_reward_schedule.pool = ctx.accounts.rewards_pool.key();
+    custom_reward_schedule.bonus_rate_per_second = bonus_rate_per_second;
+    custom_reward_schedule.start_timestamp = start_timestamp;
+    custom_reward_schedule.end_timestamp = end_timestamp;
+    custom_reward_schedule.claimed_bonus = 0;
+    custom_reward_schedule.bump = *ctx.bumps.get("custom_reward_schedule").unwrap();
+
+    Ok(())
+}
+
+// Unclaimed bonus is forfeited when the grant is removed
+pub fn remove_custom_reward_schedule(_ctx: Context<RemoveCustomRewardSchedule>) -> Result<()> {
+    Ok(())
+}
+
+// Part of an epoch's rewards earned on the SOL backing vSOL, pro rata to
+// total_staked; rounds down
+fn vsol_reward_share(rewards: u64, vsol_backing: u64, total_staked: u64) -> Result<u64> {
+    let share = (rewards as u128)
+        .checked_mul(vsol_backing as u128)
+        .ok_or(VaultSolError::MathOverflow)?
+        .checked_div(total_staked as u128)
+        .ok_or(VaultSolError::MathOverflow)?;
+
+    u64::try_from(share).map_err(|_| VaultSolError::MathOverflow.into())
+}
+
+// Scales SOL per vSOL by the growth of the SOL backing it; rounds down
+fn appreciated_exchange_rate(exchange_rate_numerator: u64, old_backing: u64, new_backing: u64) -> Result<u64> {
+    let rate = (exchange_rate_numerator as u128)
+        .checked_mul(new_backing as u128)
+        .ok_or(VaultSolError::MathOverflow)?
+        .checked_div(old_backing as u128)
+        .ok_or(VaultSolError::MathOverflow)?;
+
+    u64::try_from(rate).map_err(|_| VaultSolError::MathOverflow.into())
+}
+
+fn delegated_stake(stake_info: &AccountInfo) -> Result<u64> {
+    require!(
+        *stake_info.owner == stake::program::ID,
+        VaultSolError::InvalidStakeAccount
+    );
+    let stake_state = StakeState::deserialize(&mut &stake_info.try_borrow_data()?[..])
+        .map_err(|_| VaultSolError::InvalidStakeAccount)?;
+    let delegation = stake_state
+        .delegation()
+        .ok_or(VaultSolError::InvalidStakeAccount)?;
+
+    Ok(delegation.stake)
+}
+
+pub fn register_validator_stake_account(ctx: Context<RegisterValidatorStakeAccount>) -> Result<()> {
+    let record = &mut ctx.accounts.validator_stake_record;
+
+    // Growth is measured from the stake delegated at registration, so
+    // neither the initial delegation nor a late registration counts as reward
+    record.stake_account = ctx.accounts.stake_account.key();
+    record.last_stake = delegated_stake(&ctx.accounts.stake_account.to_account_info())?;
+    record.bump = *ctx.bumps.get("validator_stake_record").unwrap();
+
+    Ok(())
+}
+
+pub fn process_validator_rewards(
+    ctx: Context<ProcessValidatorRewards>,
+    validator_stake_accounts: Vec<Pubkey>,
+    epoch: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    // Only once per epoch, and only for the epoch that is currently running
+    require!(
+        epoch == Clock::get()?.epoch && epoch > config.last_processed_epoch,
+        VaultSolError::EpochAlreadyProcessed
+    );
+    require!(
+        ctx.remaining_accounts.len() == validator_stake_accounts.len() * 2,
+        VaultSolError::InvalidStakeAccount
+    );
+
+    // Epoch rewards are auto-delegated, so the growth of each registered
+    // stake account since its last snapshot is the reward it earned
+    let mut rewards: u64 = 0;
+    for (pair, expected_key) in ctx.remaining_accounts.chunks(2).zip(validator_stake_accounts.iter()) {
+        let (stake_info, record_info) = (&pair[0], &pair[1]);
+        require!(
+            stake_info.key() == *expected_key && record_info.is_writable,
+            VaultSolError::InvalidStakeAccount
+        );
+
+        let mut record = Account::<ValidatorStakeRecord>::try_from(record_info)?;
+        require!(
+            record.stake_account == stake_info.key(),
+            VaultSolError::InvalidStakeAccount
+        );
+
+        let current_stake = delegated_stake(stake_info)?;
+        rewards = rewards
+            .checked_add(current_stake.saturating_sub(record.last_stake))
+            .ok_or(VaultSolError::MathOverflow)?;
+
+        record.last_stake = current_stake;
+        record.exit(&crate::ID)?;
+    }
+
+    if rewards > 0 && config.vsol_backing > 0 {
+        // Stake positions earn from the rewards pool instead, so vSOL only
+        // appreciates by the share of rewards earned on its own backing
+        let vsol_rewards = vsol_reward_share(rewards, config.vsol_backing, config.total_staked)?;
+        let new_backing = config.vsol_backing
+            .checked_add(vsol_rewards)
+            .ok_or(VaultSolError::MathOverflow)?;
+
+        config.exchange_rate_numerator = appreciated_exchange_rate(
+            config.exchange_rate_numerator,
+            config.vsol_backing,
+            new_backing,
+        )?;
+        config.vsol_backing = new_backing;
+        config.total_staked = config.total_staked
+            .checked_add(vsol_rewards)
+            .ok_or(VaultSolError::MathOverflow)?;
+    }
+
+    config.last_processed_epoch = epoch;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vsol_earns_only_the_rewards_on_its_backing() {
+        // 30 of 100 SOL staked is backing vSOL, the rest is stake positions
+        assert_eq!(vsol_reward_share(1_000_000, 30, 100).unwrap(), 300_000);
+        assert_eq!(vsol_reward_share(1_000_000, 100, 100).unwrap(), 1_000_000);
+        assert_eq!(vsol_reward_share(10, 1, 3).unwrap(), 3);
+    }
+
+    #[test]
+    fn exchange_rate_follows_the_backing_growth() {
+        // Backing grows 5%
+        assert_eq!(
+            appreciated_exchange_rate(EXCHANGE_RATE_SCALE, 100_000_000_000, 105_000_000_000).unwrap(),
+            1_050_000_000
+        );
+        assert_eq!(appreciated_exchange_rate(EXCHANGE_RATE_SCALE, 3, 4).unwrap(), 1_333_333_333);
+    }
+
+    #[test]
+    fn exchange_rate_rejects_values_past_u64() {
+        assert_eq!(
+            appreciated_exchange_rate(u64::MAX, 1, 2).unwrap_err(),
+            VaultSolError::MathOverflow.into()
+        );
+    }
+}
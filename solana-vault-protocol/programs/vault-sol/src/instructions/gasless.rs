@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke_signed, system_instruction};
+use crate::state::{GlobalPause, VaultConfig, UserPosition};
+use crate::errors::VaultSolError;
+use crate::utils::lamports_after_debit;
+
+#[derive(Accounts)]
+pub struct SetAutoGaslessMode<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_position", user.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.owner == user.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user_key: Pubkey)]
+pub struct SubsidizeGasFees<'info> {
+    #[account(
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultSolError::VaultPaused,
+        constraint = config.authority == keeper.key() @ VaultSolError::InvalidAuthority,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ VaultSolError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
+    #[account(
+        seeds = [b"user_position", user_key.as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.owner == user_key,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"gas_subsidy_reserve"],
+        bump,
+        constraint = gas_subsidy_reserve.key() == config.gas_subsidy_reserve @ VaultSolError::InvalidAuthority,
+    )]
+    pub gas_subsidy_reserve: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = user.key() == user_key @ VaultSolError::InvalidAuthority,
+    )]
+    pub user: SystemAccount<'info>,
+
+    pub keeper: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_auto_gasless_mode(
+    ctx: Context<SetAutoGaslessMode>,
+    enabled: bool,
+) -> Result<()> {
+    let user_position = &mut ctx.accounts.user_position;
+    user_position.auto_gasless_mode = enabled;
+    Ok(())
+}
+
+pub fn subsidize_gas_fees(
+    ctx: Context<SubsidizeGasFees>,
+    _user_key: Pubkey,
+    transaction_fee_lamports: u64,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let user_position = &ctx.accounts.user_position;
+
+    require!(user_position.auto_gasless_mode, VaultSolError::GaslessModeDisabled);
+    check_subsidy(
+        transaction_fee_lamports,
+        config.max_subsidy_per_tx,
+        user_position.amount_staked,
+        config.min_stake_for_gas_subsidy,
+    )?;
+
+    // The system program refuses to leave the reserve below its rent-exempt
+    // minimum, so turn that into a clear error up front
+    lamports_after_debit(
+        &ctx.accounts.gas_subsidy_reserve.to_account_info(),
+        transaction_fee_lamports,
+        &Rent::get()?,
+        VaultSolError::InsufficientBalance,
+        VaultSolError::RentExemptionViolation,
+    )?;
+
+    // Refund the fee from the reserve PDA to the user
+    let reserve_bump = *ctx.bumps.get("gas_subsidy_reserve").unwrap();
+    invoke_signed(
+        &system_instruction::transfer(
+            ctx.accounts.gas_subsidy_reserve.key,
+            ctx.accounts.user.key,
+            transaction_fee_lamports,
+        ),
+        &[
+            ctx.accounts.gas_subsidy_reserve.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[&[b"gas_subsidy_reserve", &[reserve_bump]]],
+    )?;
+
+    Ok(())
+}
+
+// A subsidy is capped per transaction and only paid to positions staking
+// more than the configured minimum
+fn check_subsidy(
+    transaction_fee_lamports: u64,
+    max_subsidy_per_tx: u64,
+    amount_staked: u64,
+    min_stake_for_gas_subsidy: u64,
+) -> Result<()> {
+    require!(transaction_fee_lamports > 0, VaultSolError::InvalidAmount);
+    require!(
+        transaction_fee_lamports <= max_subsidy_per_tx,
+        VaultSolError::SubsidyTooLarge
+    );
+    require!(
+        amount_staked > min_stake_for_gas_subsidy,
+        VaultSolError::BelowSubsidyThreshold
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subsidies_up_to_the_cap_are_paid() {
+        assert!(check_subsidy(5_000, 5_000, 2_000, 1_000).is_ok());
+        assert!(check_subsidy(1, 5_000, 2_000, 1_000).is_ok());
+    }
+
+    #[test]
+    fn subsidies_above_the_cap_are_rejected() {
+        assert_eq!(
+            check_subsidy(5_001, 5_000, 2_000, 1_000).unwrap_err(),
+            VaultSolError::SubsidyTooLarge.into()
+        );
+        assert_eq!(
+            check_subsidy(0, 5_000, 2_000, 1_000).unwrap_err(),
+            VaultSolError::InvalidAmount.into()
+        );
+    }
+
+    #[test]
+    fn only_stakes_above_the_minimum_are_eligible() {
+        assert_eq!(
+            check_subsidy(5_000, 5_000, 1_000, 1_000).unwrap_err(),
+            VaultSolError::BelowSubsidyThreshold.into()
+        );
+        assert_eq!(
+            check_subsidy(5_000, 5_000, 999, 1_000).unwrap_err(),
+            VaultSolError::BelowSubsidyThreshold.into()
+        );
+        assert!(check_subsidy(5_000, 5_000, 1_001, 1_000).is_ok());
+    }
+}
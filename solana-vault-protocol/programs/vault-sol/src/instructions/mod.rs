@@ -1,7 +1,11 @@
 pub mod admin;
 pub mod staking;
 pub mod rewards;
+pub mod gasless;
+pub mod stats;
 
 pub use admin::*;
 pub use staking::*;
-pub use rewards::*;
\ No newline at end of file
+pub use rewards::*;
+pub use gasless::*;
+pub use stats::*;
\ No newline at end of file
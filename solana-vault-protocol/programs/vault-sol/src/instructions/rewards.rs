@@ -1,6 +1,11 @@
 use anchor_lang::prelude::*;
-use crate::state::{VaultConfig, StakePosition, RewardsPool};
+use crate::state::{GlobalPause, VaultConfig, StakePosition, RewardsPool, SolVault, ProtocolStats, CustomUserRewardSchedule, UserTier, REWARD_CLAIM_BIT};
 use crate::errors::VaultSolError;
+use vault_math::apply_bps;
+use crate::view::REWARD_RUNWAY_VIEW_VERSION;
+use anchor_lang::solana_program::program::set_return_data;
+use vault_math::view::encode_view_response;
+use crate::utils::{safe_debit_lamports, platform_fee_bps_for, tier_deposit_cap};
 
 #[derive(Accounts)]
 pub struct ClaimRewards<'info> {
@@ -8,9 +13,17 @@ pub struct ClaimRewards<'info> {
         seeds = [b"vault_sol_config"],
         bump = config.bump,
         constraint = !config.paused @ VaultSolError::VaultPaused,
+        constraint = config.pause_flags & (1 << REWARD_CLAIM_BIT) == 0 @ VaultSolError::OperationPaused,
     )]
     pub config: Account<'info, VaultConfig>,
 
+    #[account(
+        seeds = [b"global_pause"],
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ VaultSolError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"stake_position", user.key().as_ref()],
@@ -26,12 +39,107 @@ pub struct ClaimRewards<'info> {
     )]
     pub rewards_pool: Account<'info, RewardsPool>,
 
+    // Only users with an admin-granted bonus pass this account
+    #[account(
+        mut,
+        seeds = [b"custom_reward_schedule", user.key().as_ref()],
+        bump = custom_reward_schedule.bump,
+    )]
+    pub custom_reward_schedule: Option<Account<'info, CustomUserRewardSchedule>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct GetRewardRunway<'info> {
+    #[account(
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"rewards_pool"],
+        bump = rewards_pool.bump,
+    )]
+    pub rewards_pool: Account<'info, RewardsPool>,
+}
+
+#[derive(Accounts)]
+pub struct CompoundRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultSolError::VaultPaused,
+        constraint = config.pause_flags & (1 << REWARD_CLAIM_BIT) == 0 @ VaultSolError::OperationPaused,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        seeds = [b"global_pause"],
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ VaultSolError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_position", user.key().as_ref()],
+        bump = stake_position.bump,
+        constraint = stake_position.owner == user.key(),
+    )]
+    pub stake_position: Account<'info, StakePosition>,
+
+    #[account(
+        mut,
+        seeds = [b"rewards_pool"],
+        bump = rewards_pool.bump,
+    )]
+    pub rewards_pool: Account<'info, RewardsPool>,
+
+    // Only users with an admin-granted bonus pass this account
+    #[account(
+        mut,
+        seeds = [b"custom_reward_schedule", user.key().as_ref()],
+        bump = custom_reward_schedule.bump,
+    )]
+    pub custom_reward_schedule: Option<Account<'info, CustomUserRewardSchedule>>,
+
+    // Users without a tier account are treated as tier 0 (anonymous)
+    #[account(
+        seeds = [b"user_tier", user.key().as_ref()],
+        bump = user_tier.bump,
+    )]
+    pub user_tier: Option<Account<'info, UserTier>>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+
+    pub user: Signer<'info>,
+}
+
 pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
     let config = &ctx.accounts.config;
     let stake_position = &mut ctx.accounts.stake_position;
@@ -43,24 +151,228 @@ pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
     // Now we can safely mutably borrow rewards_pool
     let rewards_pool = &mut ctx.accounts.rewards_pool;
     
-    // Calculate rewards based on staking duration
     let current_time = Clock::get()?.unix_timestamp;
+
+    // The cooldown only applies to claims that move rewards out of the vault
+    require!(
+        current_time
+            .checked_sub(stake_position.last_reward_claim)
+            .ok_or(VaultSolError::MathOverflow)?
+            >= config.min_claim_interval_seconds,
+        VaultSolError::ClaimCooldownActive
+    );
+
+    let (total_rewards, bonus_amount, reward_amount) = pending_rewards(
+        config,
+        stake_position,
+        rewards_pool,
+        rewards_pool_info.lamports(),
+        ctx.accounts.custom_reward_schedule.as_deref(),
+        ctx.accounts.user.key,
+        current_time,
+    )?;
+
+    // Leave dust to keep accruing instead of paying it out
+    if reward_amount < config.dust_threshold {
+        return Ok(());
+    }
+
+    // Transfer rewards to user
+    safe_debit_lamports(&rewards_pool_info, reward_amount, &Rent::get()?)?;
+
+    **user_info.try_borrow_mut_lamports()? = user_info
+        .lamports()
+        .checked_add(reward_amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+
+    // Update rewards pool state
+    rewards_pool.distributed_rewards = rewards_pool.distributed_rewards
+        .checked_add(total_rewards)
+        .ok_or(VaultSolError::MathOverflow)?;
+
+    if let Some(custom_reward_schedule) = ctx.accounts.custom_reward_schedule.as_deref_mut() {
+        custom_reward_schedule.claimed_bonus = custom_reward_schedule.claimed_bonus
+            .checked_add(bonus_amount)
+            .ok_or(VaultSolError::MathOverflow)?;
+    }
+
+    // Update last claim timestamp
+    stake_position.last_reward_claim = current_time;
+
+    // Update protocol stats
+    let protocol_stats = &mut ctx.accounts.protocol_stats;
+    protocol_stats.total_rewards_distributed_all_time = protocol_stats.total_rewards_distributed_all_time
+        .checked_add(reward_amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+    protocol_stats.last_updated_slot = Clock::get()?.slot;
+
+    Ok(())
+}
+
+// Restakes net rewards into the position instead of paying them out. No
+// funds leave the vault, so the claim cooldown does not apply
+pub fn compound_rewards(ctx: Context<CompoundRewards>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let stake_position = &mut ctx.accounts.stake_position;
+
+    let rewards_pool_info = ctx.accounts.rewards_pool.to_account_info();
+    let sol_vault_info = ctx.accounts.sol_vault.to_account_info();
+    let rewards_pool = &mut ctx.accounts.rewards_pool;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    let (total_rewards, bonus_amount, reward_amount) = pending_rewards(
+        config,
+        stake_position,
+        rewards_pool,
+        rewards_pool_info.lamports(),
+        ctx.accounts.custom_reward_schedule.as_deref(),
+        ctx.accounts.user.key,
+        current_time,
+    )?;
+
+    // Leave dust to keep accruing instead of compounding it
+    if reward_amount < config.dust_threshold {
+        return Ok(());
+    }
+
+    // Compounding grows the position like a deposit, so it stays within the
+    // user's KYC tier cap
+    let position_total = stake_position.amount
+        .checked_add(reward_amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+    require!(
+        position_total <= tier_deposit_cap(config, ctx.accounts.user_tier.as_deref()),
+        VaultSolError::TierDepositCapExceeded
+    );
+
+    // Move the rewards into the vault alongside the staked principal
+    safe_debit_lamports(&rewards_pool_info, reward_amount, &Rent::get()?)?;
+
+    **sol_vault_info.try_borrow_mut_lamports()? = sol_vault_info
+        .lamports()
+        .checked_add(reward_amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+
+    stake_position.amount = stake_position.amount
+        .checked_add(reward_amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+    stake_position.last_reward_claim = current_time;
+
+    config.total_staked = config.total_staked
+        .checked_add(reward_amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+
+    rewards_pool.distributed_rewards = rewards_pool.distributed_rewards
+        .checked_add(total_rewards)
+        .ok_or(VaultSolError::MathOverflow)?;
+
+    if let Some(custom_reward_schedule) = ctx.accounts.custom_reward_schedule.as_deref_mut() {
+        custom_reward_schedule.claimed_bonus = custom_reward_schedule.claimed_bonus
+            .checked_add(bonus_amount)
+            .ok_or(VaultSolError::MathOverflow)?;
+    }
+
+    let protocol_stats = &mut ctx.accounts.protocol_stats;
+    protocol_stats.total_rewards_distributed_all_time = protocol_stats.total_rewards_distributed_all_time
+        .checked_add(reward_amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+    protocol_stats.peak_tvl = std::cmp::max(protocol_stats.peak_tvl, config.total_staked);
+    protocol_stats.last_updated_slot = Clock::get()?.slot;
+
+    Ok(())
+}
+
+// View instruction: seconds until the undistributed reserve runs out at the
+// current base emission rate (u64::MAX when nothing is emitting)
+pub fn get_reward_runway(ctx: Context<GetRewardRunway>) -> Result<()> {
+    let rewards_pool = &ctx.accounts.rewards_pool;
+
+    let reward_reserve = rewards_pool.total_rewards
+        .saturating_sub(rewards_pool.distributed_rewards);
+
+    let runway_seconds = reward_runway(
+        reward_reserve,
+        ctx.accounts.config.total_staked,
+        rewards_pool.apy_points,
+    )?;
+
+    msg!("Reward reserve: {}", reward_reserve);
+    msg!("Runway seconds: {}", runway_seconds);
+    set_return_data(&encode_view_response(REWARD_RUNWAY_VIEW_VERSION, &runway_seconds)?);
+
+    Ok(())
+}
+
+// Seconds the reserve lasts at the base APY on the total stake. Large
+// depositor boosts are applied per claim and not included here
+fn reward_runway(reward_reserve: u64, total_staked: u64, apy_points: u16) -> Result<u64> {
+    let emission_per_second = (total_staked as u128)
+        .checked_mul(apy_points as u128)
+        .ok_or(VaultSolError::MathOverflow)?
+        .checked_div(365 * 24 * 60 * 60 * 10000)
+        .ok_or(VaultSolError::MathOverflow)? as u64;
+
+    Ok(reward_reserve
+        .checked_div(emission_per_second)
+        .unwrap_or(u64::MAX))
+}
+
+// Rewards accrued since the last claim as (total debited from the pool,
+// custom bonus included in it, net paid to the user after fees)
+fn pending_rewards(
+    config: &VaultConfig,
+    stake_position: &StakePosition,
+    rewards_pool: &Account<RewardsPool>,
+    rewards_pool_lamports: u64,
+    custom_reward_schedule: Option<&CustomUserRewardSchedule>,
+    user: &Pubkey,
+    current_time: i64,
+) -> Result<(u64, u64, u64)> {
+    // Calculate rewards based on staking duration
     let time_staked = current_time
         .checked_sub(stake_position.last_reward_claim)
         .ok_or(VaultSolError::MathOverflow)?;
     
     require!(time_staked > 0, VaultSolError::InvalidAmount);
 
+    // Large positions earn a boosted APY, evaluated at claim time; kept as
+    // u128 since the boost can push it past u16
+    let apy_points = if stake_position.amount >= config.large_depositor_boost.threshold_amount {
+        (rewards_pool.apy_points as u128)
+            .checked_mul(10000 + config.large_depositor_boost.boost_bps as u128)
+            .ok_or(VaultSolError::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(VaultSolError::MathOverflow)?
+    } else {
+        rewards_pool.apy_points as u128
+    };
+
     // Calculate rewards based on amount, time, and APY
     let rewards = calculate_rewards(
         stake_position.amount,
         time_staked,
-        rewards_pool.apy_points,
+        apy_points,
     )?;
 
+    // Custom grants are paid in full on top of the fee-bearing rewards
+    let bonus_amount = match custom_reward_schedule {
+        Some(custom_reward_schedule) => {
+            require_keys_eq!(
+                custom_reward_schedule.pool,
+                rewards_pool.key(),
+                VaultSolError::InvalidCustomRewardSchedule
+            );
+            accrued_custom_bonus(custom_reward_schedule, current_time)?
+        },
+        None => 0,
+    };
+    let total_rewards = rewards
+        .checked_add(bonus_amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+
     // Validate rewards pool has enough SOL balance
     require!(
-        rewards_pool_info.lamports() >= rewards,
+        rewards_pool_lamports >= total_rewards,
         VaultSolError::InsufficientRewards
     );
 
@@ -69,57 +381,79 @@ pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
         rewards_pool.total_rewards
             .checked_sub(rewards_pool.distributed_rewards)
             .ok_or(VaultSolError::MathOverflow)?
-            >= rewards,
+            >= total_rewards,
         VaultSolError::InsufficientRewards
     );
 
     // Apply platform fee
-    let fee_amount = (rewards as u128)
-        .checked_mul(config.platform_fee_bps as u128)
-        .ok_or(VaultSolError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(VaultSolError::MathOverflow)? as u64;
-
-    let reward_amount = rewards
-        .checked_sub(fee_amount)
+    let fee_amount = apply_bps(rewards, platform_fee_bps_for(config, user))
         .ok_or(VaultSolError::MathOverflow)?;
 
-    // Transfer rewards to user
-    **rewards_pool_info.try_borrow_mut_lamports()? = rewards_pool_info
-        .lamports()
-        .checked_sub(reward_amount)
+    let reward_amount = total_rewards
+        .checked_sub(fee_amount)
         .ok_or(VaultSolError::MathOverflow)?;
 
-    **user_info.try_borrow_mut_lamports()? = user_info
-        .lamports()
-        .checked_add(reward_amount)
-        .ok_or(VaultSolError::MathOverflow)?;
+    Ok((total_rewards, bonus_amount, reward_amount))
+}
 
-    // Update rewards pool state
-    rewards_pool.distributed_rewards = rewards_pool.distributed_rewards
-        .checked_add(rewards)
-        .ok_or(VaultSolError::MathOverflow)?;
+// Bonus accrued under a custom grant and not yet claimed
+pub fn accrued_custom_bonus(
+    custom_reward_schedule: &CustomUserRewardSchedule,
+    current_time: i64,
+) -> Result<u64> {
+    let accrual_end = current_time.min(custom_reward_schedule.end_timestamp);
+    if accrual_end <= custom_reward_schedule.start_timestamp {
+        return Ok(0);
+    }
 
-    // Update last claim timestamp
-    stake_position.last_reward_claim = current_time;
+    let earned_bonus = (custom_reward_schedule.bonus_rate_per_second as u128)
+        .checked_mul((accrual_end - custom_reward_schedule.start_timestamp) as u128)
+        .ok_or(VaultSolError::MathOverflow)? as u64;
 
-    Ok(())
+    Ok(earned_bonus.saturating_sub(custom_reward_schedule.claimed_bonus))
 }
 
 // Helper function to calculate rewards
 fn calculate_rewards(
     amount: u64,
     time_staked: i64,
-    apy_points: u16,
+    apy_points: u128,
 ) -> Result<u64> {
     // Calculate rewards based on APY
     let rewards = (amount as u128)
         .checked_mul(time_staked as u128)
         .ok_or(VaultSolError::MathOverflow)?
-        .checked_mul(apy_points as u128)
+        .checked_mul(apy_points)
         .ok_or(VaultSolError::MathOverflow)?
         .checked_div(365 * 24 * 60 * 60 * 10000)  // Convert APY to per-second rate
         .ok_or(VaultSolError::MathOverflow)? as u64;
 
     Ok(rewards)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewards_allow_boosted_apy_above_u16() {
+        let year = 365 * 24 * 60 * 60;
+        assert_eq!(calculate_rewards(1_000, year, 100_000).unwrap(), 10_000);
+        assert_eq!(calculate_rewards(1_000, year / 2, 500).unwrap(), 25);
+    }
+
+    #[test]
+    fn runway_divides_the_reserve_by_the_emission_rate() {
+        // 10% APY on 3_153_600_000_000 lamports emits 10_000 lamports a second
+        assert_eq!(reward_runway(1_000_000, 3_153_600_000_000, 1000).unwrap(), 100);
+        assert_eq!(reward_runway(1_005_000, 3_153_600_000_000, 1000).unwrap(), 100);
+    }
+
+    #[test]
+    fn runway_is_unbounded_when_nothing_is_emitting() {
+        assert_eq!(reward_runway(1_000_000, 0, 1000).unwrap(), u64::MAX);
+        assert_eq!(reward_runway(1_000_000, 3_153_600_000_000, 0).unwrap(), u64::MAX);
+        // Stake too small to emit a whole lamport a second
+        assert_eq!(reward_runway(1_000_000, 1_000, 1000).unwrap(), u64::MAX);
+    }
+}
@@ -1,18 +1,29 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{program::invoke, system_instruction};
+use anchor_lang::solana_program::{program::invoke, program_option::COption, system_instruction};
 use anchor_spl::token::{Mint, Token, TokenAccount};
-use crate::state::{VaultConfig, UserPosition, StakePosition};
+use crate::state::{GlobalPause, VaultConfig, SolVault, UserPosition, StakePosition, UserTier, ProtocolStats, UserPositionCounter, DEPOSIT_BIT, WITHDRAWAL_BIT, EXCHANGE_RATE_SCALE};
 use crate::errors::VaultSolError;
+use vault_math::apply_bps;
+use crate::utils::{safe_debit_lamports, platform_fee_bps_for, tier_deposit_cap};
 
 #[derive(Accounts)]
 pub struct StakeSol<'info> {
     #[account(
+        mut,
         seeds = [b"vault_sol_config"],
         bump = config.bump,
         constraint = !config.paused @ VaultSolError::VaultPaused,
+        constraint = config.pause_flags & (1 << DEPOSIT_BIT) == 0 @ VaultSolError::OperationPaused,
     )]
     pub config: Account<'info, VaultConfig>,
 
+    #[account(
+        seeds = [b"global_pause"],
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ VaultSolError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         init_if_needed,
         payer = user,
@@ -22,19 +33,46 @@ pub struct StakeSol<'info> {
     )]
     pub user_position: Account<'info, UserPosition>,
 
+    // Users without a tier account are treated as tier 0 (anonymous)
+    #[account(
+        seeds = [b"user_tier", user.key().as_ref()],
+        bump = user_tier.bump,
+    )]
+    pub user_tier: Option<Account<'info, UserTier>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + std::mem::size_of::<UserPositionCounter>(),
+        seeds = [b"user_position_counter", user.key().as_ref()],
+        bump
+    )]
+    pub user_position_counter: Account<'info, UserPositionCounter>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
     // LST Token accounts
+    #[account(
+        constraint = vsol_mint.mint_authority == COption::Some(config.key()) @ VaultSolError::InvalidMintAuthority
+    )]
     pub vsol_mint: Account<'info, Mint>,
     #[account(mut)]
     pub user_vsol_account: Account<'info, TokenAccount>,
     
     #[account(
         mut,
-        constraint = treasury.key() == config.treasury @ VaultSolError::InvalidAuthority
+        seeds = [b"sol_vault"],
+        bump = sol_vault.bump,
     )]
-    pub treasury: SystemAccount<'info>,
+    pub sol_vault: Account<'info, SolVault>,
 
     // System accounts
     pub system_program: Program<'info, System>,
@@ -45,12 +83,21 @@ pub struct StakeSol<'info> {
 #[derive(Accounts)]
 pub struct UnstakeSol<'info> {
     #[account(
+        mut,
         seeds = [b"vault_sol_config"],
         bump = config.bump,
         constraint = !config.paused @ VaultSolError::VaultPaused,
+        constraint = config.pause_flags & (1 << WITHDRAWAL_BIT) == 0 @ VaultSolError::OperationPaused,
     )]
     pub config: Account<'info, VaultConfig>,
 
+    #[account(
+        seeds = [b"global_pause"],
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ VaultSolError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"user_position", user.key().as_ref()],
@@ -59,15 +106,37 @@ pub struct UnstakeSol<'info> {
     )]
     pub user_position: Account<'info, UserPosition>,
 
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
     // LST Token accounts
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = vsol_mint.mint_authority == COption::Some(config.key()) @ VaultSolError::InvalidMintAuthority
+    )]
     pub vsol_mint: Account<'info, Mint>,
     #[account(mut)]
     pub user_vsol_account: Account<'info, TokenAccount>,
-    #[account(mut)]
+
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+
+    // Receives the platform fee
+    #[account(
+        mut,
+        constraint = treasury.key() == config.treasury @ VaultSolError::InvalidAuthority
+    )]
     pub treasury: SystemAccount<'info>,
 
     // System accounts
@@ -78,12 +147,21 @@ pub struct UnstakeSol<'info> {
 #[derive(Accounts)]
 pub struct CreateStake<'info> {
     #[account(
+        mut,
         seeds = [b"vault_sol_config"],
         bump = config.bump,
         constraint = !config.paused @ VaultSolError::VaultPaused,
+        constraint = config.pause_flags & (1 << DEPOSIT_BIT) == 0 @ VaultSolError::OperationPaused,
     )]
     pub config: Account<'info, VaultConfig>,
 
+    #[account(
+        seeds = [b"global_pause"],
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ VaultSolError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         init,
         payer = user,
@@ -93,23 +171,59 @@ pub struct CreateStake<'info> {
     )]
     pub stake_position: Account<'info, StakePosition>,
 
+    // Users without a tier account are treated as tier 0 (anonymous)
+    #[account(
+        seeds = [b"user_tier", user.key().as_ref()],
+        bump = user_tier.bump,
+    )]
+    pub user_tier: Option<Account<'info, UserTier>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + std::mem::size_of::<UserPositionCounter>(),
+        seeds = [b"user_position_counter", user.key().as_ref()],
+        bump
+    )]
+    pub user_position_counter: Account<'info, UserPositionCounter>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut)]
-    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, SolVault>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
 pub struct WithdrawStake<'info> {
     #[account(
+        mut,
         seeds = [b"vault_sol_config"],
         bump = config.bump,
         constraint = !config.paused @ VaultSolError::VaultPaused,
+        constraint = config.pause_flags & (1 << WITHDRAWAL_BIT) == 0 @ VaultSolError::OperationPaused,
     )]
     pub config: Account<'info, VaultConfig>,
 
+    #[account(
+        seeds = [b"global_pause"],
+        bump = global_pause.bump,
+        constraint = !global_pause.paused @ VaultSolError::GloballyPaused,
+    )]
+    pub global_pause: Account<'info, GlobalPause>,
+
     #[account(
         mut,
         seeds = [b"stake_position", user.key().as_ref()],
@@ -119,58 +233,152 @@ pub struct WithdrawStake<'info> {
     )]
     pub stake_position: Account<'info, StakePosition>,
 
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut)]
-    pub treasury: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"sol_vault"],
+        bump = sol_vault.bump,
+    )]
+    pub sol_vault: Account<'info, SolVault>,
+}
+
+// vSOL is priced at exchange_rate_numerator / EXCHANGE_RATE_SCALE SOL; both
+// conversions round down, in the vault's favor
+fn sol_to_vsol(exchange_rate_numerator: u64, sol_amount: u64) -> Result<u64> {
+    Ok((sol_amount as u128)
+        .checked_mul(EXCHANGE_RATE_SCALE as u128)
+        .ok_or(VaultSolError::MathOverflow)?
+        .checked_div(exchange_rate_numerator as u128)
+        .ok_or(VaultSolError::MathOverflow)? as u64)
+}
+
+fn vsol_to_sol(exchange_rate_numerator: u64, vsol_amount: u64) -> Result<u64> {
+    u64::try_from(
+        (vsol_amount as u128)
+            .checked_mul(exchange_rate_numerator as u128)
+            .ok_or(VaultSolError::MathOverflow)?
+            / EXCHANGE_RATE_SCALE as u128,
+    )
+    .map_err(|_| VaultSolError::MathOverflow.into())
+}
+
+// Counts a newly opened position, and the user once on their first ever
+// position, whether it is a stake position or a vSOL position
+fn record_new_position(
+    user_position_counter: &mut UserPositionCounter,
+    protocol_stats: &mut ProtocolStats,
+    user: Pubkey,
+    counter_bump: u8,
+) -> Result<()> {
+    if user_position_counter.positions_created == 0 {
+        user_position_counter.owner = user;
+        user_position_counter.bump = counter_bump;
+        protocol_stats.unique_users = protocol_stats.unique_users
+            .checked_add(1)
+            .ok_or(VaultSolError::MathOverflow)?;
+    }
+    user_position_counter.positions_created = user_position_counter.positions_created
+        .checked_add(1)
+        .ok_or(VaultSolError::MathOverflow)?;
+
+    Ok(())
 }
 
 pub fn stake_sol(ctx: Context<StakeSol>, amount: u64) -> Result<()> {
-    require!(amount > 0, VaultSolError::InvalidAmount);
-    
-    let config = &ctx.accounts.config;
+    let config = &mut ctx.accounts.config;
     let user = &ctx.accounts.user;
     let user_position = &mut ctx.accounts.user_position;
+
+    // Validate amount
+    require!(amount > 0, VaultSolError::InvalidAmount);
+    require!(
+        amount >= config.min_stake_amount,
+        VaultSolError::BelowMinimumStake
+    );
+    
+    // Enforce the deposit cap for the user's KYC tier on the position total
+    let position_total = user_position.amount_staked
+        .checked_add(amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+    require!(
+        position_total <= tier_deposit_cap(config, ctx.accounts.user_tier.as_deref()),
+        VaultSolError::TierDepositCapExceeded
+    );
     
     // Transfer SOL from user to vault
     invoke(
         &system_instruction::transfer(
             user.key,
-            &config.treasury,
+            &ctx.accounts.sol_vault.key(),
             amount
         ),
         &[ 
             user.to_account_info(),
-            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.sol_vault.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
         ],
     )?;
 
+    let vsol_amount = sol_to_vsol(config.exchange_rate_numerator, amount)?;
+    require!(vsol_amount > 0, VaultSolError::InvalidAmount);
+
     // Update user position
     if user_position.owner == Pubkey::default() {
         user_position.owner = user.key();
         user_position.provider_used = config.active_provider;
         user_position.bump = *ctx.bumps.get("user_position").unwrap();
+
+        record_new_position(
+            &mut ctx.accounts.user_position_counter,
+            &mut ctx.accounts.protocol_stats,
+            user.key(),
+            *ctx.bumps.get("user_position_counter").unwrap(),
+        )?;
     }
     
     user_position.amount_staked = user_position.amount_staked.checked_add(amount)
         .ok_or(VaultSolError::MathOverflow)?;
-    user_position.vsol_minted = user_position.vsol_minted.checked_add(amount)
+    user_position.vsol_minted = user_position.vsol_minted.checked_add(vsol_amount)
         .ok_or(VaultSolError::MathOverflow)?;
     user_position.deposit_timestamp = Clock::get()?.unix_timestamp;
 
-    // Mint vSOL to user
+    config.total_staked = config.total_staked
+        .checked_add(amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+    config.vsol_backing = config.vsol_backing
+        .checked_add(amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+
+    // Update protocol stats
+    let protocol_stats = &mut ctx.accounts.protocol_stats;
+    protocol_stats.total_deposits_all_time = protocol_stats.total_deposits_all_time
+        .checked_add(amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+    protocol_stats.peak_tvl = std::cmp::max(protocol_stats.peak_tvl, config.total_staked);
+    protocol_stats.last_updated_slot = Clock::get()?.slot;
+
+    // Mint vSOL to user, signed by the config PDA as mint authority
+    let config_seeds: &[&[u8]] = &[b"vault_sol_config", &[config.bump]];
     anchor_spl::token::mint_to(
-        CpiContext::new(
+        CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
             anchor_spl::token::MintTo {
                 mint: ctx.accounts.vsol_mint.to_account_info(),
                 to: ctx.accounts.user_vsol_account.to_account_info(),
                 authority: config.to_account_info(),
             },
+            &[config_seeds],
         ),
-        amount,
+        vsol_amount,
     )?;
 
     Ok(())
@@ -179,7 +387,7 @@ pub fn stake_sol(ctx: Context<StakeSol>, amount: u64) -> Result<()> {
 pub fn unstake_sol(ctx: Context<UnstakeSol>, amount: u64) -> Result<()> {
     require!(amount > 0, VaultSolError::InvalidAmount);
     
-    let config = &ctx.accounts.config;
+    let config = &mut ctx.accounts.config;
     let user_position = &mut ctx.accounts.user_position;
     
     require!(
@@ -187,14 +395,12 @@ pub fn unstake_sol(ctx: Context<UnstakeSol>, amount: u64) -> Result<()> {
         VaultSolError::InsufficientBalance
     );
 
-    // Calculate fees
-    let fee_amount = (amount as u128)
-        .checked_mul(config.platform_fee_bps as u128)
-        .ok_or(VaultSolError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(VaultSolError::MathOverflow)? as u64;
+    // Redeem at the current exchange rate, then charge fees on the SOL value
+    let sol_amount = vsol_to_sol(config.exchange_rate_numerator, amount)?;
+    let fee_amount = apply_bps(sol_amount, platform_fee_bps_for(config, ctx.accounts.user.key))
+        .ok_or(VaultSolError::MathOverflow)?;
     
-    let withdraw_amount = amount.checked_sub(fee_amount)
+    let withdraw_amount = sol_amount.checked_sub(fee_amount)
         .ok_or(VaultSolError::MathOverflow)?;
 
     // Burn vSOL
@@ -210,28 +416,38 @@ pub fn unstake_sol(ctx: Context<UnstakeSol>, amount: u64) -> Result<()> {
         amount,
     )?;
 
-    // Transfer SOL back to user
-    **ctx.accounts.treasury.try_borrow_mut_lamports()? = ctx
-        .accounts
-        .treasury
-        .lamports()
-        .checked_sub(withdraw_amount)
-        .ok_or(VaultSolError::InsufficientBalance)?;
+    // Pay the user out of the vault and the fee to the treasury
+    safe_debit_lamports(&ctx.accounts.sol_vault.to_account_info(), sol_amount, &Rent::get()?)?;
     **ctx.accounts.user.try_borrow_mut_lamports()? = ctx
         .accounts
         .user
         .lamports()
         .checked_add(withdraw_amount)
         .ok_or(VaultSolError::MathOverflow)?;
-
-    // Update user position
-    user_position.amount_staked = user_position.amount_staked
-        .checked_sub(amount)
+    **ctx.accounts.treasury.try_borrow_mut_lamports()? = ctx
+        .accounts
+        .treasury
+        .lamports()
+        .checked_add(fee_amount)
         .ok_or(VaultSolError::MathOverflow)?;
+
+    // Update user position; amount_staked is principal, so redeeming
+    // accrued value past it simply zeroes it
+    user_position.amount_staked = user_position.amount_staked.saturating_sub(sol_amount);
     user_position.vsol_minted = user_position.vsol_minted
         .checked_sub(amount)
         .ok_or(VaultSolError::MathOverflow)?;
 
+    config.total_staked = config.total_staked.saturating_sub(sol_amount);
+    config.vsol_backing = config.vsol_backing.saturating_sub(sol_amount);
+
+    // Update protocol stats
+    let protocol_stats = &mut ctx.accounts.protocol_stats;
+    protocol_stats.total_withdrawals_all_time = protocol_stats.total_withdrawals_all_time
+        .checked_add(sol_amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+    protocol_stats.last_updated_slot = Clock::get()?.slot;
+
     Ok(())
 }
 
@@ -254,16 +470,23 @@ pub fn create_stake(
         VaultSolError::InsufficientBalance
     );
 
-    // Transfer SOL from user to vault treasury
+    // Enforce the deposit cap for the user's KYC tier; the stake position
+    // is new, so the amount is the position total
+    require!(
+        amount <= tier_deposit_cap(config, ctx.accounts.user_tier.as_deref()),
+        VaultSolError::TierDepositCapExceeded
+    );
+
+    // Transfer SOL from user to the vault
     anchor_lang::solana_program::program::invoke(
         &anchor_lang::solana_program::system_instruction::transfer(
             user.key,
-            &ctx.accounts.treasury.key(),
+            &ctx.accounts.sol_vault.key(),
             amount,
         ),
         &[ 
             user.to_account_info(),
-            ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.sol_vault.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
         ],
     )?;
@@ -284,6 +507,21 @@ pub fn create_stake(
         .checked_add(1)
         .ok_or(VaultSolError::MathOverflow)?;
 
+    record_new_position(
+        &mut ctx.accounts.user_position_counter,
+        &mut ctx.accounts.protocol_stats,
+        user.key(),
+        *ctx.bumps.get("user_position_counter").unwrap(),
+    )?;
+
+    // Update protocol stats
+    let protocol_stats = &mut ctx.accounts.protocol_stats;
+    protocol_stats.total_deposits_all_time = protocol_stats.total_deposits_all_time
+        .checked_add(amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+    protocol_stats.peak_tvl = std::cmp::max(protocol_stats.peak_tvl, config.total_staked);
+    protocol_stats.last_updated_slot = Clock::get()?.slot;
+
     Ok(())
 }
 
@@ -301,19 +539,8 @@ pub fn withdraw_stake(
         VaultSolError::InsufficientBalance
     );
 
-    // Validate treasury account matches config
-    require!(
-        ctx.accounts.treasury.key() == config.treasury,
-        VaultSolError::InvalidAuthority
-    );
-
     // Transfer SOL back to user
-    **ctx.accounts.treasury.try_borrow_mut_lamports()? = ctx
-        .accounts
-        .treasury
-        .lamports()
-        .checked_sub(amount)
-        .ok_or(VaultSolError::MathOverflow)?;
+    safe_debit_lamports(&ctx.accounts.sol_vault.to_account_info(), amount, &Rent::get()?)?;
 
     **ctx.accounts.user.try_borrow_mut_lamports()? = ctx
         .accounts
@@ -333,5 +560,41 @@ pub fn withdraw_stake(
             .ok_or(VaultSolError::MathOverflow)?;
     }
 
+    // Update protocol stats
+    let protocol_stats = &mut ctx.accounts.protocol_stats;
+    protocol_stats.total_withdrawals_all_time = protocol_stats.total_withdrawals_all_time
+        .checked_add(amount)
+        .ok_or(VaultSolError::MathOverflow)?;
+    protocol_stats.last_updated_slot = Clock::get()?.slot;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_are_one_to_one_at_initial_rate() {
+        assert_eq!(sol_to_vsol(EXCHANGE_RATE_SCALE, 5_000_000_000).unwrap(), 5_000_000_000);
+        assert_eq!(vsol_to_sol(EXCHANGE_RATE_SCALE, 5_000_000_000).unwrap(), 5_000_000_000);
+    }
+
+    #[test]
+    fn conversions_follow_appreciated_rate_and_round_down() {
+        // 1.05 SOL per vSOL
+        let rate = 1_050_000_000;
+        assert_eq!(sol_to_vsol(rate, 1_050_000_000).unwrap(), 1_000_000_000);
+        assert_eq!(vsol_to_sol(rate, 1_000_000_000).unwrap(), 1_050_000_000);
+        assert_eq!(sol_to_vsol(rate, 1).unwrap(), 0);
+
+        // A round trip never returns more SOL than was deposited
+        let vsol = sol_to_vsol(rate, 7_777_777_777).unwrap();
+        assert!(vsol_to_sol(rate, vsol).unwrap() <= 7_777_777_777);
+    }
+
+    #[test]
+    fn vsol_to_sol_rejects_overflow() {
+        assert!(vsol_to_sol(u64::MAX, u64::MAX).is_err());
+    }
+}
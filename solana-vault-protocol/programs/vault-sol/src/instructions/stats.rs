@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use crate::state::{VaultConfig, ProtocolStats};
+use crate::view::PROTOCOL_STATS_VIEW_VERSION;
+use anchor_lang::solana_program::program::set_return_data;
+use vault_math::view::encode_view_response;
+
+// Vaults initialized before protocol stats existed create them here; new
+// vaults get them from initialize_vault
+#[derive(Accounts)]
+pub struct CreateProtocolStatsAccount<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"vault_sol_config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, VaultConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<ProtocolStats>(),
+        seeds = [b"protocol_stats"],
+        bump
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetProtocolStats<'info> {
+    #[account(
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+}
+
+pub fn create_protocol_stats_account(ctx: Context<CreateProtocolStatsAccount>) -> Result<()> {
+    let protocol_stats = &mut ctx.accounts.protocol_stats;
+
+    protocol_stats.total_deposits_all_time = 0;
+    protocol_stats.total_withdrawals_all_time = 0;
+    protocol_stats.total_rewards_distributed_all_time = 0;
+    protocol_stats.unique_users = 0;
+    protocol_stats.peak_tvl = ctx.accounts.config.total_staked;
+    protocol_stats.last_updated_slot = Clock::get()?.slot;
+    protocol_stats.bump = *ctx.bumps.get("protocol_stats").unwrap();
+
+    Ok(())
+}
+
+// View instruction: logs every aggregate counter and returns the
+// ProtocolStats account as return data
+pub fn get_protocol_stats(ctx: Context<GetProtocolStats>) -> Result<()> {
+    let protocol_stats = &ctx.accounts.protocol_stats;
+
+    msg!("Total deposits: {}", protocol_stats.total_deposits_all_time);
+    msg!("Total withdrawals: {}", protocol_stats.total_withdrawals_all_time);
+    msg!("Total rewards distributed: {}", protocol_stats.total_rewards_distributed_all_time);
+    msg!("Unique users: {}", protocol_stats.unique_users);
+    msg!("Peak TVL: {}", protocol_stats.peak_tvl);
+    msg!("Last updated slot: {}", protocol_stats.last_updated_slot);
+    set_return_data(&encode_view_response(PROTOCOL_STATS_VIEW_VERSION, &**protocol_stats)?);
+
+    Ok(())
+}
@@ -3,6 +3,8 @@ use anchor_lang::prelude::*;
 pub mod state;
 pub mod errors;
 pub mod instructions;
+pub mod utils;
+pub mod view;
 
 use instructions::*;
 
@@ -35,6 +37,109 @@ pub mod vault_sol {
         instructions::admin::add_rewards(ctx, amount)
     }
 
+    pub fn add_gas_subsidy_funds(
+        ctx: Context<AddGasSubsidyFunds>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::admin::add_gas_subsidy_funds(ctx, amount)
+    }
+
+    pub fn update_gas_subsidy_params(
+        ctx: Context<UpdateGasSubsidyParams>,
+        max_subsidy_per_tx: u64,
+        min_stake_for_gas_subsidy: u64,
+    ) -> Result<()> {
+        instructions::admin::update_gas_subsidy_params(ctx, max_subsidy_per_tx, min_stake_for_gas_subsidy)
+    }
+
+    pub fn set_user_tier(
+        ctx: Context<SetUserTier>,
+        user_key: Pubkey,
+        tier: u8,
+    ) -> Result<()> {
+        instructions::admin::set_user_tier(ctx, user_key, tier)
+    }
+
+    pub fn set_tier_deposit_caps(
+        ctx: Context<SetTierDepositCaps>,
+        caps: [u64; 4],
+    ) -> Result<()> {
+        instructions::admin::set_tier_deposit_caps(ctx, caps)
+    }
+
+    pub fn set_max_pool_fee_bps(
+        ctx: Context<SetMaxPoolFeeBps>,
+        max_pool_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_max_pool_fee_bps(ctx, max_pool_fee_bps)
+    }
+
+    pub fn set_dust_threshold(
+        ctx: Context<SetDustThreshold>,
+        dust_threshold: u64,
+    ) -> Result<()> {
+        instructions::admin::set_dust_threshold(ctx, dust_threshold)
+    }
+
+    pub fn set_min_claim_interval(
+        ctx: Context<SetMinClaimInterval>,
+        min_claim_interval_seconds: i64,
+    ) -> Result<()> {
+        instructions::admin::set_min_claim_interval(ctx, min_claim_interval_seconds)
+    }
+
+    pub fn set_fee_exemption(
+        ctx: Context<SetFeeExemption>,
+        account: Pubkey,
+        exempt: bool,
+    ) -> Result<()> {
+        instructions::admin::set_fee_exemption(ctx, account, exempt)
+    }
+
+    pub fn set_reward_boost_for_large_depositors(
+        ctx: Context<SetRewardBoostForLargeDepositors>,
+        threshold_amount: u64,
+        boost_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_reward_boost_for_large_depositors(ctx, threshold_amount, boost_bps)
+    }
+
+    pub fn set_custom_reward_schedule_for_user(
+        ctx: Context<SetCustomRewardScheduleForUser>,
+        user: Pubkey,
+        bonus_rate_per_second: u64,
+        start_timestamp: i64,
+        end_timestamp: i64,
+    ) -> Result<()> {
+        instructions::admin::set_custom_reward_schedule_for_user(
+            ctx,
+            user,
+            bonus_rate_per_second,
+            start_timestamp,
+            end_timestamp,
+        )
+    }
+
+    pub fn remove_custom_reward_schedule(ctx: Context<RemoveCustomRewardSchedule>) -> Result<()> {
+        instructions::admin::remove_custom_reward_schedule(ctx)
+    }
+
+    pub fn register_validator_stake_account(ctx: Context<RegisterValidatorStakeAccount>) -> Result<()> {
+        instructions::admin::register_validator_stake_account(ctx)
+    }
+
+    pub fn process_validator_rewards(
+        ctx: Context<ProcessValidatorRewards>,
+        validator_stake_accounts: Vec<Pubkey>,
+        epoch: u64,
+    ) -> Result<()> {
+        instructions::admin::process_validator_rewards(ctx, validator_stake_accounts, epoch)
+    }
+
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, flags: u8) -> Result<()> {
+        instructions::admin::set_pause_flags(ctx, flags)
+    }
+
     pub fn pause_vault(ctx: Context<PauseVault>) -> Result<()> {
         instructions::admin::pause_vault(ctx)
     }
@@ -43,7 +148,33 @@ pub mod vault_sol {
         instructions::admin::unpause_vault(ctx)
     }
 
+    pub fn initialize_global_pause(
+        ctx: Context<InitializeGlobalPause>,
+        guardian: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::initialize_global_pause(ctx, guardian)
+    }
+
+    pub fn set_guardian(
+        ctx: Context<SetGuardian>,
+        new_guardian: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::set_guardian(ctx, new_guardian)
+    }
+
+    pub fn set_global_pause(ctx: Context<SetGlobalPause>, paused: bool) -> Result<()> {
+        instructions::admin::set_global_pause(ctx, paused)
+    }
+
     // Staking instructions
+    pub fn stake_sol(ctx: Context<StakeSol>, amount: u64) -> Result<()> {
+        instructions::staking::stake_sol(ctx, amount)
+    }
+
+    pub fn unstake_sol(ctx: Context<UnstakeSol>, amount: u64) -> Result<()> {
+        instructions::staking::unstake_sol(ctx, amount)
+    }
+
     pub fn create_stake(
         ctx: Context<CreateStake>,
         amount: u64,
@@ -62,5 +193,38 @@ pub mod vault_sol {
     pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
         instructions::rewards::claim_rewards(ctx)
     }
+
+    pub fn compound_rewards(ctx: Context<CompoundRewards>) -> Result<()> {
+        instructions::rewards::compound_rewards(ctx)
+    }
+
+    pub fn get_reward_runway(ctx: Context<GetRewardRunway>) -> Result<()> {
+        instructions::rewards::get_reward_runway(ctx)
+    }
+
+    // Gasless instructions
+    pub fn set_auto_gasless_mode(
+        ctx: Context<SetAutoGaslessMode>,
+        enabled: bool,
+    ) -> Result<()> {
+        instructions::gasless::set_auto_gasless_mode(ctx, enabled)
+    }
+
+    pub fn subsidize_gas_fees(
+        ctx: Context<SubsidizeGasFees>,
+        user_key: Pubkey,
+        transaction_fee_lamports: u64,
+    ) -> Result<()> {
+        instructions::gasless::subsidize_gas_fees(ctx, user_key, transaction_fee_lamports)
+    }
+
+    // Stats instructions
+    pub fn create_protocol_stats_account(ctx: Context<CreateProtocolStatsAccount>) -> Result<()> {
+        instructions::stats::create_protocol_stats_account(ctx)
+    }
+
+    pub fn get_protocol_stats(ctx: Context<GetProtocolStats>) -> Result<()> {
+        instructions::stats::get_protocol_stats(ctx)
+    }
 }
 
@@ -1,5 +1,21 @@
 use anchor_lang::prelude::*;
 
+// Hard ceiling on max_pool_fee_bps (10%)
+pub const MAX_PLATFORM_FEE_BPS: u16 = 1000;
+
+// Capacity of the platform fee exemption list
+pub const MAX_FEE_EXEMPT_ACCOUNTS: usize = 8;
+
+// Fixed-point scale of exchange_rate_numerator
+pub const EXCHANGE_RATE_SCALE: u64 = 1_000_000_000;
+
+// Bit positions in pause_flags
+pub const DEPOSIT_BIT: u8 = 0;
+pub const WITHDRAWAL_BIT: u8 = 1;
+pub const REWARD_CLAIM_BIT: u8 = 5;
+// Every defined bit; set_pause_flags rejects the rest
+pub const PAUSE_FLAGS_MASK: u8 = (1 << DEPOSIT_BIT) | (1 << WITHDRAWAL_BIT) | (1 << REWARD_CLAIM_BIT);
+
 #[account]
 pub struct VaultConfig {
     pub authority: Pubkey,
@@ -10,6 +26,38 @@ pub struct VaultConfig {
     pub stakers_count: u64,
     pub active_provider: LSTProvider,
     pub paused: bool,
+    pub pause_flags: u8,  // Per-operation pause bits, see *_BIT
+    pub bump: u8,
+    pub gas_subsidy_reserve: Pubkey,
+    pub max_subsidy_per_tx: u64,
+    pub min_stake_for_gas_subsidy: u64,
+    pub tier_deposit_caps: [u64; 4],  // Max stake per KYC tier (0 = anonymous)
+    pub exchange_rate_numerator: u64,  // SOL per vSOL, multiplied by 1e9
+    pub last_processed_epoch: u64,
+    pub dust_threshold: u64,  // Claims below this net amount are deferred
+    pub large_depositor_boost: LargeDepositorBoost,
+    pub fee_exempt: [Pubkey; MAX_FEE_EXEMPT_ACCOUNTS],  // Accounts charged no fee by any program
+    pub fee_exempt_count: u8,
+    pub min_claim_interval_seconds: i64,  // Cooldown between reward claims, 0 = none
+    pub max_pool_fee_bps: u16,  // Fee limit for every program in the protocol
+    pub vsol_backing: u64,  // Part of total_staked redeemable through vSOL
+}
+
+// Protocol-wide pause honored by every program in the protocol; the
+// guardian can halt them all with a single instruction
+#[account]
+pub struct GlobalPause {
+    pub guardian: Pubkey,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+// Delegated stake of one validator stake account as of the last processed
+// epoch; rewards are the growth of each registered account on its own
+#[account]
+pub struct ValidatorStakeRecord {
+    pub stake_account: Pubkey,
+    pub last_stake: u64,
     pub bump: u8,
 }
 
@@ -31,6 +79,59 @@ pub struct RewardsPool {
     pub bump: u8,
 }
 
+// Holds staked SOL. The program owns it, so unstakes and withdrawals can
+// debit it directly; the treasury only ever receives fees
+#[account]
+pub struct SolVault {
+    pub bump: u8,
+}
+
+// Aggregate counters for off-chain indexers, updated by stake, withdraw and
+// claim instructions
+#[account]
+pub struct ProtocolStats {
+    pub total_deposits_all_time: u64,
+    pub total_withdrawals_all_time: u64,
+    pub total_rewards_distributed_all_time: u64,
+    pub unique_users: u64,
+    pub peak_tvl: u64,
+    pub last_updated_slot: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct UserPositionCounter {
+    pub owner: Pubkey,
+    pub positions_created: u64,
+    pub bump: u8,
+}
+
+// Admin-granted bonus paid on top of standard staking rewards
+#[account]
+pub struct CustomUserRewardSchedule {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub bonus_rate_per_second: u64,  // Lamports per second
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub claimed_bonus: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct UserTier {
+    pub user_key: Pubkey,
+    pub tier: u8,  // KYC level, 0-3
+    pub verified_at_slot: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct LargeDepositorBoost {
+    pub threshold_amount: u64,   // Position size at which the boost applies
+    pub boost_bps: u16,          // Extra APY in bps of the base rate
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum LSTProvider {
     None,
@@ -47,5 +148,6 @@ pub struct UserPosition {
     pub provider_used: LSTProvider,
     pub deposit_timestamp: i64,
     pub bump: u8,
+    pub auto_gasless_mode: bool,  // Opt-in to fee subsidies from protocol revenue
 }
 
@@ -0,0 +1,168 @@
+use anchor_lang::prelude::*;
+use crate::errors::VaultSolError;
+use crate::state::{VaultConfig, UserTier};
+
+// Debits lamports from an account this program owns, refusing to leave it
+// below the rent-exempt minimum for its data size
+pub fn safe_debit_lamports(account: &AccountInfo, amount: u64, rent: &Rent) -> Result<()> {
+    debit_lamports_within_rent(
+        account,
+        amount,
+        rent,
+        VaultSolError::InsufficientBalance,
+        VaultSolError::RentExemptionViolation,
+    )
+}
+
+// safe_debit_lamports for other programs in the protocol, which report a
+// short balance or a rent violation with their own errors
+pub fn debit_lamports_within_rent<E: Into<Error>>(
+    account: &AccountInfo,
+    amount: u64,
+    rent: &Rent,
+    insufficient_balance: E,
+    rent_violation: E,
+) -> Result<()> {
+    let remaining = lamports_after_debit(account, amount, rent, insufficient_balance, rent_violation)?;
+
+    **account.try_borrow_mut_lamports()? = remaining;
+    Ok(())
+}
+
+// Balance left after debiting `amount`, checked against the rent-exempt
+// minimum; for debits made through the system program instead
+pub fn lamports_after_debit<E: Into<Error>>(
+    account: &AccountInfo,
+    amount: u64,
+    rent: &Rent,
+    insufficient_balance: E,
+    rent_violation: E,
+) -> Result<u64> {
+    let remaining = match account.lamports().checked_sub(amount) {
+        Some(remaining) => remaining,
+        None => return Err(insufficient_balance.into()),
+    };
+    if remaining < rent.minimum_balance(account.data_len()) {
+        return Err(rent_violation.into());
+    }
+
+    Ok(remaining)
+}
+
+// Whether `user` is on the fee exemption list, which every program in the
+// protocol honors for the fees it charges
+pub fn is_fee_exempt(config: &VaultConfig, user: &Pubkey) -> bool {
+    config.fee_exempt[..config.fee_exempt_count as usize].contains(user)
+}
+
+// Platform fee charged to `user`; accounts on the exemption list pay none
+pub fn platform_fee_bps_for(config: &VaultConfig, user: &Pubkey) -> u16 {
+    if is_fee_exempt(config, user) {
+        0
+    } else {
+        config.platform_fee_bps
+    }
+}
+
+// Deposit cap for the user's KYC tier; users without a tier account are
+// treated as tier 0 (anonymous)
+pub fn tier_deposit_cap(config: &VaultConfig, user_tier: Option<&UserTier>) -> u64 {
+    let tier = user_tier.map_or(0, |user_tier| user_tier.tier);
+    config.tier_deposit_caps[tier as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{LSTProvider, LargeDepositorBoost, MAX_FEE_EXEMPT_ACCOUNTS, MAX_PLATFORM_FEE_BPS};
+
+    fn config(platform_fee_bps: u16) -> VaultConfig {
+        VaultConfig {
+            authority: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            platform_fee_bps,
+            min_stake_amount: 0,
+            total_staked: 0,
+            stakers_count: 0,
+            active_provider: LSTProvider::None,
+            paused: false,
+            pause_flags: 0,
+            bump: 255,
+            gas_subsidy_reserve: Pubkey::default(),
+            max_subsidy_per_tx: 0,
+            min_stake_for_gas_subsidy: 0,
+            tier_deposit_caps: [u64::MAX; 4],
+            exchange_rate_numerator: 1_000_000_000,
+            last_processed_epoch: 0,
+            dust_threshold: 0,
+            large_depositor_boost: LargeDepositorBoost { threshold_amount: 0, boost_bps: 0 },
+            fee_exempt: [Pubkey::default(); MAX_FEE_EXEMPT_ACCOUNTS],
+            fee_exempt_count: 0,
+            min_claim_interval_seconds: 0,
+            max_pool_fee_bps: MAX_PLATFORM_FEE_BPS,
+            vsol_backing: 0,
+        }
+    }
+
+    #[test]
+    fn exempt_accounts_pay_no_platform_fee() {
+        let mut config = config(500);
+        let exempt = Pubkey::new_unique();
+        let normal = Pubkey::new_unique();
+        config.fee_exempt[0] = exempt;
+        config.fee_exempt_count = 1;
+
+        assert!(is_fee_exempt(&config, &exempt));
+        assert!(!is_fee_exempt(&config, &normal));
+        assert_eq!(platform_fee_bps_for(&config, &exempt), 0);
+        assert_eq!(platform_fee_bps_for(&config, &normal), 500);
+    }
+
+    #[test]
+    fn removed_exemption_slots_are_ignored() {
+        let mut config = config(500);
+        let removed = Pubkey::new_unique();
+        config.fee_exempt[0] = removed;
+        config.fee_exempt_count = 0;
+
+        assert!(!is_fee_exempt(&config, &removed));
+        assert!(!is_fee_exempt(&config, &Pubkey::default()));
+    }
+
+    // Debits `amount` from an account holding `lamports` with `data_len`
+    // bytes of data, returning the result and the balance left behind
+    fn debit(lamports: u64, data_len: usize, amount: u64) -> (Result<()>, u64) {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut balance = lamports;
+        let mut data = vec![0u8; data_len];
+        let account = AccountInfo::new(&key, false, true, &mut balance, &mut data, &owner, false, 0);
+
+        let result = safe_debit_lamports(&account, amount, &Rent::default());
+        let remaining = account.lamports();
+        (result, remaining)
+    }
+
+    #[test]
+    fn debits_down_to_the_rent_exempt_minimum() {
+        let minimum = Rent::default().minimum_balance(100);
+        let (result, remaining) = debit(minimum + 1_000, 100, 1_000);
+        assert!(result.is_ok());
+        assert_eq!(remaining, minimum);
+    }
+
+    #[test]
+    fn debits_below_the_rent_exempt_minimum_are_rejected() {
+        let minimum = Rent::default().minimum_balance(100);
+        let (result, remaining) = debit(minimum + 1_000, 100, 1_001);
+        assert_eq!(result.unwrap_err(), VaultSolError::RentExemptionViolation.into());
+        assert_eq!(remaining, minimum + 1_000);
+    }
+
+    #[test]
+    fn debits_above_the_balance_are_rejected() {
+        let (result, remaining) = debit(1_000, 0, 1_001);
+        assert_eq!(result.unwrap_err(), VaultSolError::InsufficientBalance.into());
+        assert_eq!(remaining, 1_000);
+    }
+}
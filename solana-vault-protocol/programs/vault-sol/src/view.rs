@@ -0,0 +1,6 @@
+// Payload versions for this program's views, wrapped in a
+// vault_math::view::ViewResponse
+// GetRewardRunway: u64 seconds until the rewards reserve runs out
+pub const REWARD_RUNWAY_VIEW_VERSION: u8 = 1;
+// GetProtocolStats: the ProtocolStats account
+pub const PROTOCOL_STATS_VIEW_VERSION: u8 = 1;
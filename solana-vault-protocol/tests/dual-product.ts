@@ -0,0 +1,415 @@
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  provider,
+  admin,
+  findPda,
+  accountExists,
+  fundedKeypair,
+  expectError,
+  createMint,
+  createTokenAccount,
+  mintTo,
+  tokenBalance,
+  TOKEN_PROGRAM_ID,
+  ensureVaultSol,
+  setGlobalPause,
+  VaultSolAccounts,
+} from './setup';
+
+describe('dual-product', () => {
+  const program = anchor.workspace.DualProduct as Program;
+
+  const LST = 1_000_000_000;
+  const USDC = 1_000_000;
+  const DEPOSIT_BIT = 0;
+
+  const productConfigPDA = findPda(program, [Buffer.from('dual_product_config')]);
+  const dualConfigPDA = findPda(program, [Buffer.from('dual_config')]);
+  const dualPoolPDA = findPda(program, [Buffer.from('dual_pool')]);
+  const poolStatePDA = findPda(program, [Buffer.from('pool_state')]);
+
+  let vaultSol: VaultSolAccounts;
+  let lstMint: PublicKey;
+  let usdcMint: PublicKey;
+  let poolLstAccount: PublicKey;
+  let poolUsdcAccount: PublicKey;
+  let vaultLstAccount: PublicKey;
+  let vaultUsdcAccount: PublicKey;
+
+  interface TreasuryAccounts {
+    lstAccount: PublicKey;
+    usdcAccount: PublicKey;
+  }
+  let adminTreasury: TreasuryAccounts;
+
+  interface DualUser {
+    keypair: Keypair;
+    lstAccount: PublicKey;
+    usdcAccount: PublicKey;
+  }
+
+  async function fundedDualUser(): Promise<DualUser> {
+    const keypair = await fundedKeypair();
+    const lstAccount = await createTokenAccount(lstMint, keypair.publicKey);
+    const usdcAccount = await createTokenAccount(usdcMint, keypair.publicKey);
+    await mintTo(lstMint, lstAccount, 10 * LST);
+    await mintTo(usdcMint, usdcAccount, 1_000 * USDC);
+    return { keypair, lstAccount, usdcAccount };
+  }
+
+  const createDualPosition = (user: DualUser, lstAmount: number, usdcAmount: number) =>
+    program.methods
+      .createDualPosition(new anchor.BN(lstAmount), new anchor.BN(usdcAmount))
+      .accounts({
+        config: dualConfigPDA,
+        productConfig: productConfigPDA,
+        globalPause: vaultSol.globalPause,
+        pool: dualPoolPDA,
+        userPosition: findPda(program, [Buffer.from('user_position'), user.keypair.publicKey.toBuffer()]),
+        loyaltyProfile: findPda(program, [Buffer.from('loyalty'), user.keypair.publicKey.toBuffer()]),
+        user: user.keypair.publicKey,
+        userWsolAccount: user.lstAccount,
+        poolWsolAccount: poolLstAccount,
+        userUsdcAccount: user.usdcAccount,
+        poolUsdcAccount: poolUsdcAccount,
+        wsolMint: lstMint,
+        usdcMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user.keypair])
+      .rpc();
+
+  const depositDual = (user: DualUser, lstAmount: number, usdcAmount: number) =>
+    program.methods
+      .depositDual(new anchor.BN(lstAmount), new anchor.BN(usdcAmount))
+      .accounts({
+        config: productConfigPDA,
+        globalPause: vaultSol.globalPause,
+        userPosition: findPda(program, [Buffer.from('user_dual_position'), user.keypair.publicKey.toBuffer()]),
+        poolState: poolStatePDA,
+        loyaltyProfile: findPda(program, [Buffer.from('loyalty'), user.keypair.publicKey.toBuffer()]),
+        volumeTracker: findPda(program, [Buffer.from('user_volume_tracker'), user.keypair.publicKey.toBuffer()]),
+        user: user.keypair.publicKey,
+        userLstAccount: user.lstAccount,
+        vaultLstAccount,
+        userUsdcAccount: user.usdcAccount,
+        vaultUsdcAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user.keypair])
+      .rpc();
+
+  const addToPosition = (user: DualUser, lstAmount: number, usdcAmount: number) =>
+    program.methods
+      .addToPosition(new anchor.BN(lstAmount), new anchor.BN(usdcAmount))
+      .accounts({
+        config: dualConfigPDA,
+        productConfig: productConfigPDA,
+        globalPause: vaultSol.globalPause,
+        pool: dualPoolPDA,
+        userPosition: findPda(program, [Buffer.from('user_position'), user.keypair.publicKey.toBuffer()]),
+        loyaltyProfile: findPda(program, [Buffer.from('loyalty'), user.keypair.publicKey.toBuffer()]),
+        user: user.keypair.publicKey,
+        userWsolAccount: user.lstAccount,
+        poolWsolAccount: poolLstAccount,
+        userUsdcAccount: user.usdcAccount,
+        poolUsdcAccount: poolUsdcAccount,
+        wsolMint: lstMint,
+        usdcMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user.keypair])
+      .rpc();
+
+  const withdrawDual = (
+    user: DualUser,
+    lstAmount: number,
+    usdcAmount: number,
+    treasury: TreasuryAccounts = adminTreasury
+  ) =>
+    program.methods
+      .withdrawDual(new anchor.BN(lstAmount), new anchor.BN(usdcAmount))
+      .accounts({
+        config: productConfigPDA,
+        vaultConfig: vaultSol.config,
+        globalPause: vaultSol.globalPause,
+        userPosition: findPda(program, [Buffer.from('user_dual_position'), user.keypair.publicKey.toBuffer()]),
+        poolState: poolStatePDA,
+        volumeTracker: findPda(program, [Buffer.from('user_volume_tracker'), user.keypair.publicKey.toBuffer()]),
+        loyaltyProfile: findPda(program, [Buffer.from('loyalty'), user.keypair.publicKey.toBuffer()]),
+        user: user.keypair.publicKey,
+        lstMint,
+        userLstAccount: user.lstAccount,
+        vaultLstAccount,
+        usdcMint,
+        userUsdcAccount: user.usdcAccount,
+        vaultUsdcAccount,
+        treasuryLstAccount: treasury.lstAccount,
+        treasuryUsdcAccount: treasury.usdcAccount,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([user.keypair])
+      .rpc();
+
+  before(async () => {
+    vaultSol = await ensureVaultSol();
+
+    if (await accountExists(productConfigPDA)) {
+      const config = await program.account.dualProductConfig.fetch(productConfigPDA);
+      lstMint = config.lstRewardMint;
+      usdcMint = config.usdcRewardMint;
+    } else {
+      lstMint = await createMint(admin.publicKey, 9);
+      usdcMint = await createMint(admin.publicKey, 6);
+
+      await program.methods
+        .initializeProduct(50, new anchor.BN(1 * USDC), 5000, 5000)
+        .accounts({
+          config: productConfigPDA,
+          poolState: poolStatePDA,
+          vaultConfig: vaultSol.config,
+          authority: admin.publicKey,
+          treasury: admin.publicKey,
+          lstRewardMint: lstMint,
+          usdcRewardMint: usdcMint,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    if (!(await accountExists(dualConfigPDA))) {
+      await program.methods
+        .initializeDualProduct(50, new anchor.BN(1 * LST))
+        .accounts({
+          config: dualConfigPDA,
+          vaultConfig: vaultSol.config,
+          pool: dualPoolPDA,
+          authority: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    poolLstAccount = await createTokenAccount(lstMint, admin.publicKey);
+    poolUsdcAccount = await createTokenAccount(usdcMint, admin.publicKey);
+    vaultLstAccount = await createTokenAccount(lstMint, productConfigPDA);
+    vaultUsdcAccount = await createTokenAccount(usdcMint, productConfigPDA);
+    adminTreasury = {
+      lstAccount: await createTokenAccount(lstMint, admin.publicKey),
+      usdcAccount: await createTokenAccount(usdcMint, admin.publicKey),
+    };
+  });
+
+  describe('global pause', () => {
+    afterEach(async () => {
+      await setGlobalPause(false);
+    });
+
+    it('Blocks create_dual_position while the protocol is paused', async () => {
+      const user = await fundedDualUser();
+
+      await setGlobalPause(true);
+      await expectError(createDualPosition(user, 1 * LST, 100 * USDC), 'GloballyPaused');
+
+      await setGlobalPause(false);
+      await createDualPosition(user, 1 * LST, 100 * USDC);
+      expect(await tokenBalance(user.lstAccount)).to.equal(9 * LST);
+      expect(await tokenBalance(user.usdcAccount)).to.equal(900 * USDC);
+    });
+
+    it('Blocks withdraw_dual while the protocol is paused', async () => {
+      const user = await fundedDualUser();
+
+      await setGlobalPause(true);
+      await expectError(withdrawDual(user, 1 * LST, 0), 'GloballyPaused');
+    });
+  });
+
+  describe('withdraw_dual', () => {
+    it('Closes the emptied position and refunds its rent', async () => {
+      const user = await fundedDualUser();
+      const owner = user.keypair.publicKey;
+      const userPosition = findPda(program, [Buffer.from('user_dual_position'), owner.toBuffer()]);
+      const volumeTracker = findPda(program, [Buffer.from('user_volume_tracker'), owner.toBuffer()]);
+      const loyaltyProfile = findPda(program, [Buffer.from('loyalty'), owner.toBuffer()]);
+
+      const lamportsBefore = await provider.connection.getBalance(owner);
+      await depositDual(user, 1 * LST, 100 * USDC);
+      expect(await accountExists(userPosition)).to.be.true;
+
+      await withdrawDual(user, 1 * LST, 100 * USDC);
+
+      expect(await accountExists(userPosition)).to.be.false;
+      expect(await accountExists(volumeTracker)).to.be.false;
+      expect(await accountExists(loyaltyProfile)).to.be.false;
+      // Only transaction fees are lost once every PDA's rent comes back
+      const lamportsAfter = await provider.connection.getBalance(owner);
+      expect(lamportsBefore - lamportsAfter).to.be.below(20_000);
+    });
+
+    it('Keeps a partially withdrawn position open', async () => {
+      const user = await fundedDualUser();
+      const userPosition = findPda(program, [Buffer.from('user_dual_position'), user.keypair.publicKey.toBuffer()]);
+
+      await depositDual(user, 1 * LST, 100 * USDC);
+      await withdrawDual(user, 1 * LST, 50 * USDC);
+
+      const position = await program.account.userDualPosition.fetch(userPosition);
+      expect(position.lstAmount.toNumber()).to.equal(0);
+      expect(position.usdcAmount.toNumber()).to.equal(50 * USDC);
+    });
+  });
+
+  describe('volume tracking', () => {
+    it('Records deposit and withdrawal volume at the larger mint decimals', async () => {
+      const user = await fundedDualUser();
+      const volumeTracker = findPda(program, [Buffer.from('user_volume_tracker'), user.keypair.publicKey.toBuffer()]);
+
+      // 100 USDC at 6 decimals counts as 100 * LST at 9
+      await depositDual(user, 1 * LST, 100 * USDC);
+      let tracker = await program.account.userVolumeTracker.fetch(volumeTracker);
+      expect(tracker.rolling30dVolume.toNumber()).to.be.closeTo(101 * LST, LST / 1000);
+
+      await withdrawDual(user, 1 * LST, 0);
+      tracker = await program.account.userVolumeTracker.fetch(volumeTracker);
+      expect(tracker.rolling30dVolume.toNumber()).to.be.closeTo(102 * LST, LST / 1000);
+    });
+  });
+
+  describe('minimum deposit', () => {
+    // The minimum is valued at the LST mint's 9 decimals
+    const USDC_SCALE = LST / USDC;
+    let minDualAmount: number;
+
+    before(async () => {
+      const config = await program.account.dualConfig.fetch(dualConfigPDA);
+      minDualAmount = config.minDualAmount.toNumber();
+    });
+
+    it('Rejects a combined deposit below the minimum', async () => {
+      const user = await fundedDualUser();
+      await expectError(
+        createDualPosition(user, minDualAmount / 2, minDualAmount / 2 / USDC_SCALE - 1),
+        'BelowMinimumDeposit'
+      );
+    });
+
+    it('Rejects a single-leg deposit below the minimum', async () => {
+      const user = await fundedDualUser();
+      await expectError(
+        createDualPosition(user, 0, minDualAmount / USDC_SCALE - 1),
+        'BelowMinimumDeposit'
+      );
+    });
+
+    it('Accepts a single-leg USDC deposit worth the minimum', async () => {
+      const user = await fundedDualUser();
+      await createDualPosition(user, 0, minDualAmount / USDC_SCALE);
+      expect(await tokenBalance(user.lstAccount)).to.equal(10 * LST);
+      expect(await tokenBalance(user.usdcAccount)).to.equal(1_000 * USDC - minDualAmount / USDC_SCALE);
+    });
+
+    it('Holds top-ups to the same minimum', async () => {
+      const user = await fundedDualUser();
+      await createDualPosition(user, minDualAmount, 0);
+
+      await expectError(addToPosition(user, minDualAmount - 1, 0), 'BelowMinimumDeposit');
+      await addToPosition(user, 0, minDualAmount / USDC_SCALE);
+      expect(await tokenBalance(user.usdcAccount)).to.equal(1_000 * USDC - minDualAmount / USDC_SCALE);
+    });
+  });
+
+  describe('treasury', () => {
+    const setTreasury = (newTreasury: PublicKey, authority: Keypair | null = null) =>
+      program.methods
+        .setTreasury()
+        .accounts({
+          config: dualConfigPDA,
+          productConfig: productConfigPDA,
+          newTreasury,
+          authority: authority ? authority.publicKey : admin.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    after(async () => {
+      await setTreasury(admin.publicKey);
+    });
+
+    it('Moves the treasury to a new address', async () => {
+      const newTreasury = (await fundedKeypair(1)).publicKey;
+
+      await setTreasury(newTreasury);
+      const config = await program.account.dualConfig.fetch(dualConfigPDA);
+      expect(config.treasury.equals(newTreasury)).to.be.true;
+      const productConfig = await program.account.dualProductConfig.fetch(productConfigPDA);
+      expect(productConfig.treasury.equals(newTreasury)).to.be.true;
+    });
+
+    it('Sends withdrawal fees to the rotated treasury', async () => {
+      const newTreasury = (await fundedKeypair(1)).publicKey;
+      const rotated = {
+        lstAccount: await createTokenAccount(lstMint, newTreasury),
+        usdcAccount: await createTokenAccount(usdcMint, newTreasury),
+      };
+      await setTreasury(newTreasury);
+
+      const user = await fundedDualUser();
+      await depositDual(user, 1 * LST, 100 * USDC);
+      await expectError(withdrawDual(user, 1 * LST, 100 * USDC), 'InvalidTokenAccountOwner');
+      await withdrawDual(user, 1 * LST, 100 * USDC, rotated);
+
+      const lstFee = await tokenBalance(rotated.lstAccount);
+      const usdcFee = await tokenBalance(rotated.usdcAccount);
+      expect(lstFee).to.be.above(0);
+      expect(usdcFee).to.be.above(0);
+      expect(await tokenBalance(user.lstAccount)).to.equal(10 * LST - lstFee);
+      expect(await tokenBalance(user.usdcAccount)).to.equal(1_000 * USDC - usdcFee);
+    });
+
+    it('Rejects callers other than the config authority', async () => {
+      const outsider = await fundedKeypair();
+      await expectError(setTreasury(outsider.publicKey, outsider), 'ConstraintHasOne');
+    });
+
+    it('Rejects the default pubkey as treasury', async () => {
+      await expectError(setTreasury(PublicKey.default), 'AccountNotSystemOwned');
+    });
+  });
+
+  describe('pause flags', () => {
+    const setPauseFlags = (flags: number) =>
+      program.methods
+        .setPauseFlags(flags)
+        .accounts({ config: productConfigPDA, authority: admin.publicKey })
+        .rpc();
+
+    afterEach(async () => {
+      await setPauseFlags(0);
+    });
+
+    it('Blocks create_dual_position while deposits are paused', async () => {
+      const user = await fundedDualUser();
+
+      await setPauseFlags(1 << DEPOSIT_BIT);
+      await expectError(createDualPosition(user, 1 * LST, 100 * USDC), 'OperationPaused');
+
+      await setPauseFlags(0);
+      await createDualPosition(user, 1 * LST, 100 * USDC);
+      expect(await tokenBalance(user.lstAccount)).to.equal(9 * LST);
+    });
+
+    it('Rejects flags outside the defined bits', async () => {
+      await expectError(setPauseFlags(1 << 2), 'InvalidPauseFlags');
+      await expectError(setPauseFlags(0xff), 'InvalidPauseFlags');
+    });
+  });
+});
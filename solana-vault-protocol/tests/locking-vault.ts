@@ -0,0 +1,452 @@
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { PublicKey, Keypair, LAMPORTS_PER_SOL, SystemProgram } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  provider,
+  admin,
+  findPda,
+  accountExists,
+  fundedKeypair,
+  expectError,
+  TOKEN_PROGRAM_ID,
+  ensureVaultSol,
+  setGlobalPause,
+  simulateView,
+  vaultSolProgram,
+  VaultSolAccounts,
+} from './setup';
+
+describe('locking-vault', () => {
+  const program = anchor.workspace.LockingVault as Program;
+
+  const LOCK_PERIODS = [30, 60, 90, 180, 365];
+  const MULTIPLIERS = [10000, 11000, 12000, 15000, 20000];
+  const MIN_DEPOSIT = 0.1 * LAMPORTS_PER_SOL;
+
+  const configPDA = findPda(program, [Buffer.from('locking_vault_config')]);
+  const poolStatePDA = findPda(program, [Buffer.from('lock_pool_state')]);
+  const emissionSchedulePDA = findPda(program, [Buffer.from('reward_emission_schedule')]);
+  const solVaultPDA = findPda(program, [Buffer.from('lock_sol_vault')]);
+
+  let vaultSol: VaultSolAccounts;
+
+  const positionPda = (owner: PublicKey) =>
+    findPda(program, [Buffer.from('user_lock_position'), owner.toBuffer()]);
+
+  async function createSolLockPosition(
+    user: Keypair,
+    amount: number,
+    lockPeriod: number = LOCK_PERIODS[0]
+  ): Promise<PublicKey> {
+    const userPosition = positionPda(user.publicKey);
+    await program.methods
+      .createLockPosition(new anchor.BN(amount), { sol: {} }, lockPeriod)
+      .accounts({
+        config: configPDA,
+        globalPause: vaultSol.globalPause,
+        userPosition,
+        poolState: poolStatePDA,
+        user: user.publicKey,
+        usdcMint: null,
+        userUsdcAccount: null,
+        vaultUsdcAccount: null,
+        solVault: solVaultPDA,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+    return userPosition;
+  }
+
+  const POSITION_COUNT_VIEW_VERSION = 1;
+
+  const positionCount = async () => {
+    const { version, payload } = await simulateView(
+      program.methods.getPositionCount().accounts({ poolState: poolStatePDA })
+    );
+    expect(version).to.equal(POSITION_COUNT_VIEW_VERSION);
+    expect(payload.length).to.equal(8);
+    return new anchor.BN(payload, 'le').toNumber();
+  };
+
+  const initializeVault = (platformFeeBps: number, treasury: PublicKey = admin.publicKey) =>
+    program.methods
+      .initializeVault(platformFeeBps, new anchor.BN(MIN_DEPOSIT), LOCK_PERIODS, MULTIPLIERS)
+      .accounts({
+        config: configPDA,
+        vaultConfig: vaultSol.config,
+        poolState: poolStatePDA,
+        solVault: solVaultPDA,
+        authority: admin.publicKey,
+        treasury,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  before(async () => {
+    vaultSol = await ensureVaultSol();
+  });
+
+  // Later suites rely on the vault these create
+  describe('initialization', () => {
+    it('Rejects a platform fee above the protocol limit', async function () {
+      if (await accountExists(configPDA)) {
+        this.skip();
+      }
+      const { maxPoolFeeBps } = await vaultSolProgram().account.vaultConfig.fetch(vaultSol.config);
+
+      await expectError(initializeVault(maxPoolFeeBps + 1), 'InvalidFee');
+    });
+
+    // The default pubkey is the system program, which the SystemAccount
+    // owner check turns away before the handler's own guard runs
+    it('Rejects the default pubkey as treasury', async function () {
+      if (await accountExists(configPDA)) {
+        this.skip();
+      }
+
+      await expectError(initializeVault(500, PublicKey.default), 'AccountNotSystemOwned');
+    });
+
+    it('Initializes the vault within the protocol limit', async function () {
+      if (await accountExists(configPDA)) {
+        this.skip();
+      }
+
+      await initializeVault(500);
+      const config = await program.account.lockingVaultConfig.fetch(configPDA);
+      expect(config.platformFeeBps).to.equal(500);
+    });
+  });
+
+  describe('lock periods', () => {
+    it('Rejects a lock period table starting at zero', async () => {
+      await expectError(
+        program.methods
+          .updateLockPeriods([0, ...LOCK_PERIODS.slice(1)], MULTIPLIERS)
+          .accounts({ config: configPDA, authority: admin.publicKey })
+          .rpc(),
+        'ZeroLockDuration'
+      );
+    });
+
+    it('Rejects a zero lock period', async () => {
+      await expectError(createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT, 0), 'ZeroLockDuration');
+    });
+
+    it('Accepts each configured lock period', async () => {
+      for (const lockPeriod of LOCK_PERIODS) {
+        const userPosition = await createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT, lockPeriod);
+        const position = await program.account.userLockPosition.fetch(userPosition);
+        expect(position.lockPeriod).to.equal(lockPeriod);
+      }
+    });
+  });
+
+  describe('pool cap', () => {
+    const setMaxTotalLocked = (maxSolLocked: anchor.BN, authority: Keypair | null = null) =>
+      program.methods
+        .setMaxTotalLocked(maxSolLocked, new anchor.BN(0))
+        .accounts({
+          poolState: poolStatePDA,
+          authority: authority ? authority.publicKey : admin.publicKey,
+          config: configPDA,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    after(async () => {
+      await setMaxTotalLocked(new anchor.BN(0));
+    });
+
+    it('Fills the pool to the cap and rejects the next lock', async () => {
+      const { totalSolLocked } = await program.account.lockPoolState.fetch(poolStatePDA);
+      await setMaxTotalLocked(totalSolLocked.addn(MIN_DEPOSIT));
+
+      await createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT);
+      const poolState = await program.account.lockPoolState.fetch(poolStatePDA);
+      expect(poolState.totalSolLocked.eq(poolState.maxSolLocked)).to.be.true;
+
+      await expectError(createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT), 'LockPoolFull');
+    });
+
+    it('Lifts the cap when set back to zero', async () => {
+      await setMaxTotalLocked(new anchor.BN(0));
+      const userPosition = await createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT);
+      expect(await accountExists(userPosition)).to.be.true;
+    });
+
+    it('Rejects callers other than the vault authority', async () => {
+      await expectError(setMaxTotalLocked(new anchor.BN(1), await fundedKeypair()), 'ConstraintHasOne');
+    });
+  });
+
+  describe('emission schedule', () => {
+    it('Reads the base APY without a schedule until one is set', async () => {
+      await program.methods
+        .getCurrentEmissionRate()
+        .accounts({ poolState: poolStatePDA, emissionSchedule: null })
+        .rpc();
+    });
+
+    it('Requires the schedule once the pool has one', async () => {
+      const now = Math.floor(Date.now() / 1000);
+      await program.methods
+        .setRewardEmissionSchedule([
+          { startTimestamp: new anchor.BN(now - 60), baseApyPoints: 800 },
+        ])
+        .accounts({
+          config: configPDA,
+          poolState: poolStatePDA,
+          emissionSchedule: emissionSchedulePDA,
+          authority: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const poolState = await program.account.lockPoolState.fetch(poolStatePDA);
+      expect(poolState.emissionSchedule.equals(emissionSchedulePDA)).to.be.true;
+
+      await expectError(
+        program.methods
+          .getCurrentEmissionRate()
+          .accounts({ poolState: poolStatePDA, emissionSchedule: null })
+          .rpc(),
+        'MissingEmissionSchedule'
+      );
+
+      await program.methods
+        .getCurrentEmissionRate()
+        .accounts({ poolState: poolStatePDA, emissionSchedule: emissionSchedulePDA })
+        .rpc();
+    });
+  });
+
+  describe('reward preview', () => {
+    const PREVIEW_LOCK_REWARDS_VIEW_VERSION = 1;
+
+    const previewLockRewards = async (userPosition: PublicKey) => {
+      const { emissionSchedule } = await program.account.lockPoolState.fetch(poolStatePDA);
+      const { version, payload } = await simulateView(
+        program.methods.previewLockRewards().accounts({
+          config: configPDA,
+          vaultConfig: vaultSol.config,
+          userPosition,
+          poolState: poolStatePDA,
+          emissionSchedule: emissionSchedule.equals(PublicKey.default) ? null : emissionSchedule,
+          customRewardSchedule: null,
+        })
+      );
+      expect(version).to.equal(PREVIEW_LOCK_REWARDS_VIEW_VERSION);
+      expect(payload.length).to.equal(8);
+      return new anchor.BN(payload, 'le');
+    };
+
+    const setDustThreshold = (dustThreshold: number) =>
+      program.methods
+        .setDustThreshold(new anchor.BN(dustThreshold))
+        .accounts({ config: configPDA, authority: admin.publicKey })
+        .rpc();
+
+    // A few seconds on one SOL accrues only a handful of lamports
+    before(async () => {
+      await setDustThreshold(1);
+    });
+
+    after(async () => {
+      await setDustThreshold(1000);
+    });
+
+    it('Previews zero while the accrued rewards are dust', async () => {
+      const userPosition = await createSolLockPosition(await fundedKeypair(3), LAMPORTS_PER_SOL);
+      await new Promise(resolve => setTimeout(resolve, 2000));
+
+      await setDustThreshold(1_000_000);
+      expect((await previewLockRewards(userPosition)).isZero()).to.be.true;
+      await setDustThreshold(1);
+      expect((await previewLockRewards(userPosition)).gtn(0)).to.be.true;
+    });
+
+    it('Previews accruing rewards without touching the position', async () => {
+      const userPosition = await createSolLockPosition(await fundedKeypair(3), LAMPORTS_PER_SOL);
+      const { lastRewardClaim } = await program.account.userLockPosition.fetch(userPosition);
+
+      await new Promise(resolve => setTimeout(resolve, 3000));
+      const first = await previewLockRewards(userPosition);
+      expect(first.gtn(0)).to.be.true;
+
+      await new Promise(resolve => setTimeout(resolve, 2000));
+      const second = await previewLockRewards(userPosition);
+      expect(second.gte(first)).to.be.true;
+
+      const position = await program.account.userLockPosition.fetch(userPosition);
+      expect(position.lastRewardClaim.eq(lastRewardClaim)).to.be.true;
+    });
+  });
+
+  describe('position boost', () => {
+    const grantPositionBoost = (userPosition: PublicKey, extraBps: number, expiry: number) =>
+      program.methods
+        .grantPositionBoost(extraBps, new anchor.BN(expiry))
+        .accounts({ config: configPDA, userPosition, authority: admin.publicKey })
+        .rpc();
+
+    it('Grants a boost that expires in the future', async () => {
+      const userPosition = await createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT);
+      const expiry = Math.floor(Date.now() / 1000) + 24 * 60 * 60;
+
+      await grantPositionBoost(userPosition, 5000, expiry);
+      const position = await program.account.userLockPosition.fetch(userPosition);
+      expect(position.promoBoostBps).to.equal(5000);
+      expect(position.promoBoostExpiry.toNumber()).to.equal(expiry);
+    });
+
+    it('Rejects a boost above 1x', async () => {
+      const userPosition = await createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT);
+      const expiry = Math.floor(Date.now() / 1000) + 24 * 60 * 60;
+
+      await expectError(grantPositionBoost(userPosition, 10001, expiry), 'InvalidMultiplier');
+    });
+
+    it('Rejects a boost that has already expired', async () => {
+      const userPosition = await createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT);
+      const expiry = Math.floor(Date.now() / 1000) - 60;
+
+      await expectError(grantPositionBoost(userPosition, 5000, expiry), 'InvalidAmount');
+    });
+  });
+
+  describe('force close', () => {
+    const setForceCloseParams = (dustThreshold: number, abandonmentPeriod: number) =>
+      program.methods
+        .setForceCloseParams(new anchor.BN(dustThreshold), new anchor.BN(abandonmentPeriod))
+        .accounts({ config: configPDA, authority: admin.publicKey })
+        .rpc();
+
+    const forceClose = (userPosition: PublicKey) =>
+      program.methods
+        .forceClosePosition()
+        .accounts({
+          config: configPDA,
+          userPosition,
+          poolState: poolStatePDA,
+          treasury: admin.publicKey,
+          solVault: solVaultPDA,
+          vaultUsdcAccount: null,
+          treasuryUsdcAccount: null,
+          authority: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    it('Rejects positions at or above the force-close dust threshold', async () => {
+      const userPosition = await createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT);
+      await setForceCloseParams(MIN_DEPOSIT, 1);
+
+      await expectError(forceClose(userPosition), 'PositionNotDust');
+    });
+
+    it('Rejects positions that are still being claimed', async () => {
+      const userPosition = await createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT);
+      await setForceCloseParams(LAMPORTS_PER_SOL, 365 * 24 * 60 * 60);
+
+      await expectError(forceClose(userPosition), 'PositionNotAbandoned');
+    });
+
+    it('Closes an abandoned dust position and forfeits it to the treasury', async () => {
+      const userPosition = await createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT);
+      await setForceCloseParams(LAMPORTS_PER_SOL, 1);
+      await new Promise(resolve => setTimeout(resolve, 2000));
+
+      const before = await program.account.lockPoolState.fetch(poolStatePDA);
+      const vaultBefore = await provider.connection.getBalance(solVaultPDA);
+      await forceClose(userPosition);
+
+      expect(await accountExists(userPosition)).to.be.false;
+      const after = await program.account.lockPoolState.fetch(poolStatePDA);
+      expect(before.totalSolLocked.sub(after.totalSolLocked).toNumber()).to.equal(MIN_DEPOSIT);
+      expect(after.totalPenalties.sub(before.totalPenalties).toNumber()).to.equal(MIN_DEPOSIT);
+      expect(before.positionsCount.sub(after.positionsCount).toNumber()).to.equal(1);
+      expect(vaultBefore - (await provider.connection.getBalance(solVaultPDA))).to.equal(MIN_DEPOSIT);
+    });
+
+    it('Counts positions up on create and down on force close', async () => {
+      const countBefore = await positionCount();
+
+      const userPosition = await createSolLockPosition(await fundedKeypair(), MIN_DEPOSIT);
+      expect(await positionCount()).to.equal(countBefore + 1);
+
+      await setForceCloseParams(LAMPORTS_PER_SOL, 1);
+      await new Promise(resolve => setTimeout(resolve, 2000));
+      await forceClose(userPosition);
+      expect(await positionCount()).to.equal(countBefore);
+    });
+  });
+
+  describe('withdrawals', () => {
+    const withdrawEarly = (user: Keypair, amount: number) =>
+      program.methods
+        .withdrawLocked(new anchor.BN(amount), { early: {} })
+        .accounts({
+          config: configPDA,
+          globalPause: vaultSol.globalPause,
+          userPosition: positionPda(user.publicKey),
+          poolState: poolStatePDA,
+          user: user.publicKey,
+          userUsdcAccount: null,
+          vaultUsdcAccount: null,
+          solVault: solVaultPDA,
+          treasury: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+    it('Keeps a partially withdrawn position open', async () => {
+      const user = await fundedKeypair();
+      const userPosition = await createSolLockPosition(user, 2 * MIN_DEPOSIT);
+      const countBefore = await positionCount();
+
+      await withdrawEarly(user, MIN_DEPOSIT);
+
+      const position = await program.account.userLockPosition.fetch(userPosition);
+      expect(position.amount.toNumber()).to.equal(MIN_DEPOSIT);
+      expect(await positionCount()).to.equal(countBefore);
+    });
+
+    it('Closes and counts down a position once it is emptied', async () => {
+      const user = await fundedKeypair();
+      const userPosition = await createSolLockPosition(user, MIN_DEPOSIT);
+      const countBefore = await positionCount();
+      const vaultBefore = await provider.connection.getBalance(solVaultPDA);
+
+      await withdrawEarly(user, MIN_DEPOSIT);
+
+      expect(await accountExists(userPosition)).to.be.false;
+      expect(await positionCount()).to.equal(countBefore - 1);
+      // The user's share and the penalty both leave the vault
+      expect(vaultBefore - (await provider.connection.getBalance(solVaultPDA))).to.equal(MIN_DEPOSIT);
+    });
+  });
+
+  describe('global pause', () => {
+    afterEach(async () => {
+      await setGlobalPause(false);
+    });
+
+    it('Blocks new lock positions while the protocol is paused', async () => {
+      const user = await fundedKeypair();
+
+      await setGlobalPause(true);
+      await expectError(createSolLockPosition(user, MIN_DEPOSIT), 'GloballyPaused');
+
+      await setGlobalPause(false);
+      const userPosition = await createSolLockPosition(user, MIN_DEPOSIT);
+      expect(await accountExists(userPosition)).to.be.true;
+    });
+  });
+});
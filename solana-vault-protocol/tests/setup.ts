@@ -0,0 +1,198 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, Spl } from '@project-serum/anchor';
+import { PublicKey, Keypair, LAMPORTS_PER_SOL, SystemProgram } from '@solana/web3.js';
+import { expect } from 'chai';
+
+// Shared helpers for the program test suites. Every program except vault-sol
+// reads the protocol-wide GlobalPause owned by vault-sol, so the vault-sol
+// config, protocol stats and global pause are created once and reused.
+
+export const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+export const connection = provider.connection;
+export const admin = provider.wallet;
+export const tokenProgram = Spl.token(provider);
+export const TOKEN_PROGRAM_ID = anchor.utils.token.TOKEN_PROGRAM_ID;
+
+export const vaultSolProgram = () => anchor.workspace.VaultSol as Program;
+
+export const findPda = (program: Program, seeds: (Buffer | Uint8Array)[]): PublicKey =>
+  PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+export const accountExists = async (address: PublicKey): Promise<boolean> =>
+  (await connection.getAccountInfo(address)) !== null;
+
+export async function airdrop(to: PublicKey, sol: number = 2): Promise<void> {
+  const signature = await connection.requestAirdrop(to, sol * LAMPORTS_PER_SOL);
+  await connection.confirmTransaction(signature);
+}
+
+export async function fundedKeypair(sol: number = 2): Promise<Keypair> {
+  const keypair = Keypair.generate();
+  await airdrop(keypair.publicKey, sol);
+  return keypair;
+}
+
+// Expects the transaction to fail with the given Anchor error name, e.g.
+// 'GloballyPaused' or 'ConstraintAddress'
+export async function expectError(promise: Promise<unknown>, errorName: string): Promise<void> {
+  try {
+    await promise;
+  } catch (err: any) {
+    const code = err?.error?.errorCode?.code;
+    if (code !== undefined) {
+      expect(code).to.equal(errorName);
+    } else {
+      expect(String(err)).to.contain(errorName);
+    }
+    return;
+  }
+  expect.fail(`expected the transaction to fail with ${errorName}`);
+}
+
+export async function createMint(mintAuthority: PublicKey, decimals: number = 6): Promise<PublicKey> {
+  const mint = Keypair.generate();
+  await tokenProgram.methods
+    .initializeMint(decimals, mintAuthority, null)
+    .accounts({ mint: mint.publicKey, rent: anchor.web3.SYSVAR_RENT_PUBKEY })
+    .preInstructions([await tokenProgram.account.mint.createInstruction(mint)])
+    .signers([mint])
+    .rpc();
+  return mint.publicKey;
+}
+
+export async function createTokenAccount(mint: PublicKey, owner: PublicKey): Promise<PublicKey> {
+  const account = Keypair.generate();
+  await tokenProgram.methods
+    .initializeAccount()
+    .accounts({
+      account: account.publicKey,
+      mint,
+      authority: owner,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    })
+    .preInstructions([await tokenProgram.account.token.createInstruction(account)])
+    .signers([account])
+    .rpc();
+  return account.publicKey;
+}
+
+// Mints with the provider wallet as mint authority
+export async function mintTo(mint: PublicKey, to: PublicKey, amount: number): Promise<void> {
+  await tokenProgram.methods
+    .mintTo(new anchor.BN(amount))
+    .accounts({ mint, to, authority: admin.publicKey })
+    .rpc();
+}
+
+// Simulates a view instruction and unwraps the ViewResponse envelope
+// { version: u8, payload: Vec<u8> } it writes as return data
+export async function simulateView(
+  builder: { transaction(): Promise<anchor.web3.Transaction> }
+): Promise<{ version: number; payload: Buffer }> {
+  const tx = await builder.transaction();
+  tx.feePayer = admin.publicKey;
+  tx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
+
+  const simulation = await connection.simulateTransaction(tx);
+  expect(simulation.value.err).to.be.null;
+  const returnData = simulation.value.returnData;
+  if (!returnData) {
+    throw new Error('view instruction set no return data');
+  }
+
+  const data = Buffer.from(returnData.data[0], 'base64');
+  const payloadLength = data.readUInt32LE(1);
+  return { version: data.readUInt8(0), payload: data.subarray(5, 5 + payloadLength) };
+}
+
+export async function tokenBalance(account: PublicKey): Promise<number> {
+  const balance = await connection.getTokenAccountBalance(account);
+  return Number(balance.value.amount);
+}
+
+export interface VaultSolAccounts {
+  config: PublicKey;
+  rewardsPool: PublicKey;
+  solVault: PublicKey;
+  protocolStats: PublicKey;
+  globalPause: PublicKey;
+}
+
+export const VAULT_SOL_PLATFORM_FEE_BPS = 500;
+export const VAULT_SOL_MIN_STAKE = 0.1 * LAMPORTS_PER_SOL;
+
+const vaultSolPdas = (): VaultSolAccounts => {
+  const program = vaultSolProgram();
+  return {
+    config: findPda(program, [Buffer.from('vault_sol_config')]),
+    rewardsPool: findPda(program, [Buffer.from('rewards_pool')]),
+    solVault: findPda(program, [Buffer.from('sol_vault')]),
+    protocolStats: findPda(program, [Buffer.from('protocol_stats')]),
+    globalPause: findPda(program, [Buffer.from('global_pause')]),
+  };
+};
+
+// The vSOL mint must have the vault config as its mint authority
+export async function initializeVaultSol(vsolMint: PublicKey): Promise<void> {
+  const accounts = vaultSolPdas();
+  await vaultSolProgram().methods
+    .initializeVault(VAULT_SOL_PLATFORM_FEE_BPS, new anchor.BN(VAULT_SOL_MIN_STAKE))
+    .accounts({
+      config: accounts.config,
+      rewardsPool: accounts.rewardsPool,
+      solVault: accounts.solVault,
+      protocolStats: accounts.protocolStats,
+      vsolMint,
+      authority: admin.publicKey,
+      treasury: admin.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+}
+
+// Creates the vault-sol config, protocol stats and global pause on first use
+export async function ensureVaultSol(): Promise<VaultSolAccounts> {
+  const program = vaultSolProgram();
+  const accounts = vaultSolPdas();
+
+  if (!(await accountExists(accounts.config))) {
+    await initializeVaultSol(await createMint(accounts.config, 9));
+  }
+
+  // Only vaults initialized before protocol stats existed lack them
+  if (!(await accountExists(accounts.protocolStats))) {
+    await program.methods
+      .createProtocolStatsAccount()
+      .accounts({
+        config: accounts.config,
+        protocolStats: accounts.protocolStats,
+        authority: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  if (!(await accountExists(accounts.globalPause))) {
+    await program.methods
+      .initializeGlobalPause(admin.publicKey)
+      .accounts({
+        config: accounts.config,
+        globalPause: accounts.globalPause,
+        authority: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
+  return accounts;
+}
+
+export async function setGlobalPause(paused: boolean): Promise<void> {
+  const { globalPause } = await ensureVaultSol();
+  await vaultSolProgram().methods
+    .setGlobalPause(paused)
+    .accounts({ globalPause, guardian: admin.publicKey })
+    .rpc();
+}
@@ -0,0 +1,302 @@
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { expect } from 'chai';
+import {
+  admin,
+  findPda,
+  accountExists,
+  fundedKeypair,
+  expectError,
+  createMint,
+  createTokenAccount,
+  mintTo,
+  tokenBalance,
+  TOKEN_PROGRAM_ID,
+  ensureVaultSol,
+  setGlobalPause,
+  simulateView,
+  vaultSolProgram,
+  VaultSolAccounts,
+} from './setup';
+
+describe('stablecoin-vault', () => {
+  const program = anchor.workspace.StablecoinVault as Program;
+
+  const USDC = 1_000_000;
+  const MIN_DEPOSIT = 1 * USDC;
+
+  const configPDA = findPda(program, [Buffer.from('stable_vault_config')]);
+  const poolStatePDA = findPda(program, [Buffer.from('stable_pool_state')]);
+
+  let vaultSol: VaultSolAccounts;
+  let usdcMint: PublicKey;
+  let vaultUsdcAccount: PublicKey;
+  let user: Keypair;
+  let userUsdcAccount: PublicKey;
+
+  const depositAccounts = (vaultAccount: PublicKey) => ({
+    config: configPDA,
+    globalPause: vaultSol.globalPause,
+    userPosition: findPda(program, [Buffer.from('user_stable_position'), user.publicKey.toBuffer()]),
+    poolState: poolStatePDA,
+    ledger: findPda(program, [Buffer.from('user_position_ledger'), user.publicKey.toBuffer()]),
+    user: user.publicKey,
+    usdcMint,
+    userUsdcAccount,
+    vaultUsdcAccount: vaultAccount,
+    systemProgram: SystemProgram.programId,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+  });
+
+  const donateAccounts = (vaultAccount: PublicKey) => ({
+    config: configPDA,
+    globalPause: vaultSol.globalPause,
+    poolState: poolStatePDA,
+    donor: user.publicKey,
+    donorUsdcAccount: userUsdcAccount,
+    vaultUsdcAccount: vaultAccount,
+    tokenProgram: TOKEN_PROGRAM_ID,
+  });
+
+  const claimAccounts = (userAccount: PublicKey) => ({
+    config: configPDA,
+    vaultConfig: vaultSol.config,
+    globalPause: vaultSol.globalPause,
+    userPosition: findPda(program, [Buffer.from('user_stable_position'), user.publicKey.toBuffer()]),
+    poolState: poolStatePDA,
+    ledger: findPda(program, [Buffer.from('user_position_ledger'), user.publicKey.toBuffer()]),
+    user: user.publicKey,
+    userUsdcAccount: userAccount,
+    vaultUsdcAccount,
+    treasury: admin.publicKey,
+    systemProgram: SystemProgram.programId,
+    tokenProgram: TOKEN_PROGRAM_ID,
+  });
+
+  before(async () => {
+    vaultSol = await ensureVaultSol();
+
+    if (await accountExists(configPDA)) {
+      const config = await program.account.stablecoinVaultConfig.fetch(configPDA);
+      usdcMint = config.usdcMint;
+      vaultUsdcAccount = config.vaultUsdcAccount;
+    } else {
+      usdcMint = await createMint(admin.publicKey, 6);
+      vaultUsdcAccount = await createTokenAccount(usdcMint, configPDA);
+
+      await program.methods
+        .initializeVault(50, new anchor.BN(MIN_DEPOSIT), 5000)
+        .accounts({
+          config: configPDA,
+          vaultConfig: vaultSol.config,
+          poolState: poolStatePDA,
+          authority: admin.publicKey,
+          treasury: admin.publicKey,
+          rewardMint: usdcMint,
+          usdcMint,
+          vaultUsdcAccount,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    user = await fundedKeypair();
+    userUsdcAccount = await createTokenAccount(usdcMint, user.publicKey);
+    await mintTo(usdcMint, userUsdcAccount, 1_000 * USDC);
+  });
+
+  describe('vault USDC account', () => {
+    it('Rejects deposits into any account but the configured one', async () => {
+      const otherVaultAccount = await createTokenAccount(usdcMint, configPDA);
+
+      await expectError(
+        program.methods
+          .depositStable(new anchor.BN(100 * USDC))
+          .accounts(depositAccounts(otherVaultAccount))
+          .signers([user])
+          .rpc(),
+        'InvalidTokenAccount'
+      );
+    });
+
+    it('Accepts deposits and donations into the configured account', async () => {
+      const vaultBefore = await tokenBalance(vaultUsdcAccount);
+
+      await program.methods
+        .depositStable(new anchor.BN(100 * USDC))
+        .accounts(depositAccounts(vaultUsdcAccount))
+        .signers([user])
+        .rpc();
+
+      const depositsBefore = (await program.account.stablePoolState.fetch(poolStatePDA)).totalDeposits;
+      await program.methods
+        .donateToReserve(new anchor.BN(10 * USDC))
+        .accounts(donateAccounts(vaultUsdcAccount))
+        .signers([user])
+        .rpc();
+
+      expect(await tokenBalance(vaultUsdcAccount)).to.equal(vaultBefore + 110 * USDC);
+      const poolState = await program.account.stablePoolState.fetch(poolStatePDA);
+      expect(poolState.totalDeposits.sub(depositsBefore).toNumber()).to.equal(10 * USDC);
+    });
+
+    it('Rejects donations into any account but the configured one', async () => {
+      const otherVaultAccount = await createTokenAccount(usdcMint, configPDA);
+
+      await expectError(
+        program.methods
+          .donateToReserve(new anchor.BN(10 * USDC))
+          .accounts(donateAccounts(otherVaultAccount))
+          .signers([user])
+          .rpc(),
+        'InvalidTokenAccount'
+      );
+    });
+  });
+
+  describe('global pause', () => {
+    afterEach(async () => {
+      await setGlobalPause(false);
+    });
+
+    it('Blocks deposits while the protocol is paused', async () => {
+      await setGlobalPause(true);
+      await expectError(
+        program.methods
+          .depositStable(new anchor.BN(10 * USDC))
+          .accounts(depositAccounts(vaultUsdcAccount))
+          .signers([user])
+          .rpc(),
+        'GloballyPaused'
+      );
+
+      await setGlobalPause(false);
+      const vaultBefore = await tokenBalance(vaultUsdcAccount);
+      await program.methods
+        .depositStable(new anchor.BN(10 * USDC))
+        .accounts(depositAccounts(vaultUsdcAccount))
+        .signers([user])
+        .rpc();
+      expect(await tokenBalance(vaultUsdcAccount)).to.equal(vaultBefore + 10 * USDC);
+    });
+  });
+
+  describe('fee limit', () => {
+    const setFeeModel = (feeBps: number) =>
+      program.methods
+        .setFeeModel({ flatWithdrawal: { feeBps } })
+        .accounts({ config: configPDA, vaultConfig: vaultSol.config, authority: admin.publicKey })
+        .rpc();
+
+    it('Rejects a withdrawal fee above the protocol limit', async () => {
+      const { maxPoolFeeBps } = await vaultSolProgram().account.vaultConfig.fetch(vaultSol.config);
+
+      await expectError(setFeeModel(maxPoolFeeBps + 1), 'InvalidFee');
+      await setFeeModel(maxPoolFeeBps);
+      await setFeeModel(50);
+    });
+  });
+
+  describe('lending reward rate', () => {
+    const REWARD_INDEX_SCALE = new anchor.BN('1000000000000000000');
+    const SECONDS_PER_YEAR = 365 * 24 * 60 * 60;
+
+    const setLendingRewardRate = (apyPoints: number, authority: Keypair | null = null) =>
+      program.methods
+        .setLendingRewardRate(apyPoints)
+        .accounts({
+          config: configPDA,
+          poolState: poolStatePDA,
+          authority: authority ? authority.publicKey : admin.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    after(async () => {
+      await setLendingRewardRate(0);
+    });
+
+    it('Writes the per-second lending index at 1e18 scale', async () => {
+      await setLendingRewardRate(500);
+
+      const poolState = await program.account.stablePoolState.fetch(poolStatePDA);
+      const expected = new anchor.BN(500).mul(REWARD_INDEX_SCALE).div(new anchor.BN(SECONDS_PER_YEAR * 10000));
+      expect(poolState.stablePerShare.toString()).to.equal(expected.toString());
+    });
+
+    it('Rejects an APY above 100%', async () => {
+      await expectError(setLendingRewardRate(10001), 'InvalidAmount');
+    });
+
+    it('Rejects callers other than the vault authority', async () => {
+      await expectError(setLendingRewardRate(500, await fundedKeypair()), 'ConstraintHasOne');
+    });
+  });
+
+  describe('reward mint', () => {
+    it('Rejects claims into an account of another mint', async () => {
+      const otherMint = await createMint(admin.publicKey, 6);
+      const otherMintAccount = await createTokenAccount(otherMint, user.publicKey);
+
+      await expectError(
+        program.methods
+          .claimStableRewards({ treasury: {} })
+          .accounts(claimAccounts(otherMintAccount))
+          .signers([user])
+          .rpc(),
+        'RewardMintMismatch'
+      );
+    });
+  });
+
+  describe('destination account owner', () => {
+    let strangerUsdcAccount: PublicKey;
+
+    before(async () => {
+      strangerUsdcAccount = await createTokenAccount(usdcMint, Keypair.generate().publicKey);
+    });
+
+    it('Rejects withdrawals into an account owned by someone else', async () => {
+      await expectError(
+        program.methods
+          .withdrawStable(new anchor.BN(1 * USDC))
+          .accounts(claimAccounts(strangerUsdcAccount))
+          .signers([user])
+          .rpc(),
+        'InvalidTokenAccountOwner'
+      );
+    });
+
+    it('Rejects claims into an account owned by someone else', async () => {
+      await expectError(
+        program.methods
+          .claimStableRewards({ treasury: {} })
+          .accounts(claimAccounts(strangerUsdcAccount))
+          .signers([user])
+          .rpc(),
+        'InvalidTokenAccountOwner'
+      );
+    });
+  });
+
+  describe('position ledger view', () => {
+    const USER_POSITION_LEDGER_VIEW_VERSION = 1;
+    const LEDGER_ENTRY_SIZE = 33;
+
+    it('Returns the ledger entries in a versioned view', async () => {
+      const ledger = findPda(program, [Buffer.from('user_position_ledger'), user.publicKey.toBuffer()]);
+      const account = await program.account.userPositionLedger.fetch(ledger);
+
+      const { version, payload } = await simulateView(
+        program.methods.getUserPositionLedger().accounts({ ledger })
+      );
+
+      expect(version).to.equal(USER_POSITION_LEDGER_VIEW_VERSION);
+      const entryCount = payload.readUInt32LE(0);
+      expect(entryCount).to.equal((account.entries as unknown[]).length);
+      expect(payload.length).to.equal(4 + entryCount * LEDGER_ENTRY_SIZE);
+    });
+  });
+});
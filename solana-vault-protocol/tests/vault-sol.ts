@@ -1,13 +1,36 @@
 import * as anchor from '@project-serum/anchor';
 import { Program } from '@project-serum/anchor';
-import { PublicKey, Keypair, LAMPORTS_PER_SOL } from '@solana/web3.js';
+import {
+  PublicKey,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  SystemProgram,
+  StakeProgram,
+  Authorized,
+  Transaction,
+} from '@solana/web3.js';
 import { expect } from 'chai';
+import {
+  provider,
+  airdrop,
+  fundedKeypair,
+  findPda,
+  accountExists,
+  expectError,
+  createMint,
+  createTokenAccount,
+  tokenBalance,
+  TOKEN_PROGRAM_ID,
+  ensureVaultSol,
+  initializeVaultSol,
+  setGlobalPause,
+  simulateView,
+  VaultSolAccounts,
+  VAULT_SOL_PLATFORM_FEE_BPS,
+  VAULT_SOL_MIN_STAKE,
+} from './setup';
 
 describe('vault-sol', () => {
-  // Configure the client to use the local cluster
-  const provider = anchor.AnchorProvider.env();
-  anchor.setProvider(provider);
-
   const program = anchor.workspace.VaultSol as Program;
   const connection = provider.connection;
   
@@ -21,6 +44,8 @@ describe('vault-sol', () => {
   let vaultBump: number;
   let rewardsPDA: PublicKey;
   let rewardsBump: number;
+  let vaultSol: VaultSolAccounts;
+  let userPositionCounterPDA: PublicKey;
   
   before(async () => {
     // Airdrop SOL to the user for transactions
@@ -46,22 +71,29 @@ describe('vault-sol', () => {
     );
     rewardsPDA = rewardsPDAAddress;
     rewardsBump = rewardsBumpValue;
+
+    [userPositionCounterPDA] = await PublicKey.findProgramAddress(
+      [Buffer.from("user_position_counter"), user.publicKey.toBuffer()],
+      program.programId
+    );
+  });
+
+  it('Rejects a vSOL mint the vault is not the mint authority of', async function () {
+    if (await accountExists(vaultPDA)) {
+      this.skip();
+    }
+
+    await expectError(initializeVaultSol(await createMint(admin.publicKey, 9)), 'InvalidMintAuthority');
+    expect(await accountExists(vaultPDA)).to.be.false;
   });
 
   it('Initializes the vault', async () => {
-    const platformFeeBps = 500; // 5%
-    const minStake = 0.1 * LAMPORTS_PER_SOL;
+    const platformFeeBps = VAULT_SOL_PLATFORM_FEE_BPS;
+    const minStake = VAULT_SOL_MIN_STAKE;
 
-    await program.methods
-      .initializeVault(platformFeeBps, new anchor.BN(minStake))
-      .accounts({
-        config: vaultPDA,
-        rewardsPool: rewardsPDA,
-        authority: admin.publicKey,
-        treasury: admin.publicKey,
-        systemProgram: anchor.web3.SystemProgram.programId,
-      })
-      .rpc();
+    // Shared with the other suites, which need the global pause it creates
+    vaultSol = await ensureVaultSol();
+    expect(vaultSol.config.equals(vaultPDA)).to.be.true;
       
     // Verify the vault account was created correctly
     const vaultAccount = await program.account.vaultConfig.fetch(vaultPDA);
@@ -88,16 +120,20 @@ describe('vault-sol', () => {
     
     // Get initial balances
     const initialUserBalance = await connection.getBalance(user.publicKey);
-    const initialTreasuryBalance = await connection.getBalance(admin.publicKey);
+    const initialVaultBalance = await connection.getBalance(vaultSol.solVault);
     
     // Create stake position
     await program.methods
       .createStake(new anchor.BN(stakeAmount))
       .accounts({
         config: vaultPDA,
+        globalPause: vaultSol.globalPause,
         stakePosition: stakePDA,
+        userTier: null,
+        userPositionCounter: userPositionCounterPDA,
+        protocolStats: vaultSol.protocolStats,
         user: user.publicKey,
-        treasury: admin.publicKey,
+        solVault: vaultSol.solVault,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .signers([user])
@@ -110,9 +146,9 @@ describe('vault-sol', () => {
     
     // Verify balances changed correctly
     const newUserBalance = await connection.getBalance(user.publicKey);
-    const newTreasuryBalance = await connection.getBalance(admin.publicKey);
+    const newVaultBalance = await connection.getBalance(vaultSol.solVault);
     expect(newUserBalance).to.be.at.most(initialUserBalance - stakeAmount);
-    expect(newTreasuryBalance).to.equal(initialTreasuryBalance + stakeAmount);
+    expect(newVaultBalance).to.equal(initialVaultBalance + stakeAmount);
 
     // Verify vault state updated
     const vaultAccount = await program.account.vaultConfig.fetch(vaultPDA);
@@ -129,6 +165,7 @@ describe('vault-sol', () => {
         config: vaultPDA,
         rewardsPool: rewardsPDA,
         authority: admin.publicKey,
+        systemProgram: SystemProgram.programId,
       })
       .rpc();
 
@@ -147,8 +184,11 @@ describe('vault-sol', () => {
       .claimRewards()
       .accounts({
         config: vaultPDA,
+        globalPause: vaultSol.globalPause,
         stakePosition: stakePDA,
         rewardsPool: rewardsPDA,
+        customRewardSchedule: null,
+        protocolStats: vaultSol.protocolStats,
         user: user.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
@@ -169,31 +209,593 @@ describe('vault-sol', () => {
     const withdrawAmount = stakePosition.amount.toNumber();
     
     const initialUserBalance = await connection.getBalance(user.publicKey);
-    const initialTreasuryBalance = await connection.getBalance(admin.publicKey);
+    const initialVaultBalance = await connection.getBalance(vaultSol.solVault);
 
     // Withdraw stake
     await program.methods
       .withdrawStake(new anchor.BN(withdrawAmount))
       .accounts({
         config: vaultPDA,
+        globalPause: vaultSol.globalPause,
         stakePosition: stakePDA,
+        protocolStats: vaultSol.protocolStats,
         user: user.publicKey,
-        treasury: admin.publicKey,
+        solVault: vaultSol.solVault,
       })
       .signers([user])
       .rpc();
 
     // Verify balances changed correctly
     const newUserBalance = await connection.getBalance(user.publicKey);
-    const newTreasuryBalance = await connection.getBalance(admin.publicKey);
+    const newVaultBalance = await connection.getBalance(vaultSol.solVault);
     
     // Account for transaction fees in the check
     expect(newUserBalance).to.be.above(initialUserBalance + withdrawAmount - 0.01 * LAMPORTS_PER_SOL);
-    expect(newTreasuryBalance).to.equal(initialTreasuryBalance - withdrawAmount);
+    expect(newVaultBalance).to.equal(initialVaultBalance - withdrawAmount);
 
     // Verify vault state updated
     const vaultAccount = await program.account.vaultConfig.fetch(vaultPDA);
     expect(vaultAccount.totalStaked.toNumber()).to.equal(0);
     expect(vaultAccount.stakersCount.toNumber()).to.equal(0);
   });
-});
\ No newline at end of file
+});
+describe('vault-sol liquid staking', () => {
+  const program = anchor.workspace.VaultSol as Program;
+  const connection = provider.connection;
+  const admin = provider.wallet;
+
+  let vaultSol: VaultSolAccounts;
+  let user: Keypair;
+  let userPositionPDA: PublicKey;
+  let vsolMint: PublicKey;
+  let userVsolAccount: PublicKey;
+
+  const PROTOCOL_STATS_VIEW_VERSION = 1;
+
+  const stakeSolAccounts = (mint: PublicKey, tokenAccount: PublicKey) => ({
+    config: vaultSol.config,
+    globalPause: vaultSol.globalPause,
+    userPosition: userPositionPDA,
+    userTier: null,
+    userPositionCounter: findPda(program, [Buffer.from('user_position_counter'), user.publicKey.toBuffer()]),
+    protocolStats: vaultSol.protocolStats,
+    user: user.publicKey,
+    vsolMint: mint,
+    userVsolAccount: tokenAccount,
+    solVault: vaultSol.solVault,
+    systemProgram: SystemProgram.programId,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+  });
+
+  before(async () => {
+    vaultSol = await ensureVaultSol();
+    user = await fundedKeypair(3);
+    userPositionPDA = findPda(program, [Buffer.from('user_position'), user.publicKey.toBuffer()]);
+    vsolMint = await createMint(vaultSol.config, 9);
+    userVsolAccount = await createTokenAccount(vsolMint, user.publicKey);
+  });
+
+  it('Mints vSOL through the exchange rate', async () => {
+    const stakeAmount = 0.5 * LAMPORTS_PER_SOL;
+    const config = await program.account.vaultConfig.fetch(vaultSol.config);
+    const expectedVsol = new anchor.BN(stakeAmount)
+      .mul(new anchor.BN(LAMPORTS_PER_SOL))
+      .div(config.exchangeRateNumerator);
+
+    const statsBefore = await program.account.protocolStats.fetch(vaultSol.protocolStats);
+
+    await program.methods
+      .stakeSol(new anchor.BN(stakeAmount))
+      .accounts(stakeSolAccounts(vsolMint, userVsolAccount))
+      .signers([user])
+      .rpc();
+
+    expect(await tokenBalance(userVsolAccount)).to.equal(expectedVsol.toNumber());
+    const position = await program.account.userPosition.fetch(userPositionPDA);
+    expect(position.vsolMinted.toNumber()).to.equal(expectedVsol.toNumber());
+    expect(position.amountStaked.toNumber()).to.equal(stakeAmount);
+
+    // vSOL deposits count towards the protocol stats like stake positions
+    const statsAfter = await program.account.protocolStats.fetch(vaultSol.protocolStats);
+    expect(statsAfter.totalDepositsAllTime.sub(statsBefore.totalDepositsAllTime).toNumber()).to.equal(stakeAmount);
+    expect(statsAfter.uniqueUsers.sub(statsBefore.uniqueUsers).toNumber()).to.equal(1);
+  });
+
+  it('Returns the protocol stats as a versioned view', async () => {
+    const stats = await program.account.protocolStats.fetch(vaultSol.protocolStats);
+    const { version, payload } = await simulateView(
+      program.methods.getProtocolStats().accounts({ protocolStats: vaultSol.protocolStats })
+    );
+
+    expect(version).to.equal(PROTOCOL_STATS_VIEW_VERSION);
+    // ProtocolStats is borsh-encoded as consecutive u64 counters
+    const u64At = (offset: number) => new anchor.BN(payload.subarray(offset, offset + 8), 'le');
+    expect(u64At(0).eq(stats.totalDepositsAllTime)).to.be.true;
+    expect(u64At(24).eq(stats.uniqueUsers)).to.be.true;
+    expect(u64At(32).eq(stats.peakTvl)).to.be.true;
+  });
+
+  it('Rejects a vSOL mint the vault does not control', async () => {
+    const foreignMint = await createMint(admin.publicKey, 9);
+    const foreignAccount = await createTokenAccount(foreignMint, user.publicKey);
+
+    await expectError(
+      program.methods
+        .stakeSol(new anchor.BN(0.5 * LAMPORTS_PER_SOL))
+        .accounts(stakeSolAccounts(foreignMint, foreignAccount))
+        .signers([user])
+        .rpc(),
+      'InvalidMintAuthority'
+    );
+  });
+
+  it('Enforces the minimum stake amount', async () => {
+    await expectError(
+      program.methods
+        .stakeSol(new anchor.BN(VAULT_SOL_MIN_STAKE - 1))
+        .accounts(stakeSolAccounts(vsolMint, userVsolAccount))
+        .signers([user])
+        .rpc(),
+      'BelowMinimumStake'
+    );
+
+    const before = await program.account.userPosition.fetch(userPositionPDA);
+    await program.methods
+      .stakeSol(new anchor.BN(VAULT_SOL_MIN_STAKE))
+      .accounts(stakeSolAccounts(vsolMint, userVsolAccount))
+      .signers([user])
+      .rpc();
+
+    const after = await program.account.userPosition.fetch(userPositionPDA);
+    expect(after.amountStaked.sub(before.amountStaked).toNumber()).to.equal(VAULT_SOL_MIN_STAKE);
+  });
+
+  it('Rejects registering an account that is not a stake account', async () => {
+    const notStake = Keypair.generate().publicKey;
+    await airdrop(notStake, 1);
+
+    await expectError(
+      program.methods
+        .registerValidatorStakeAccount()
+        .accounts({
+          config: vaultSol.config,
+          validatorStakeRecord: findPda(program, [Buffer.from('validator_stake'), notStake.toBuffer()]),
+          stakeAccount: notStake,
+          authority: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+      'InvalidStakeAccount'
+    );
+  });
+
+  it('Does not count a newly delegated stake account as rewards', async () => {
+    const stakeAccount = Keypair.generate();
+    const delegated = 2 * LAMPORTS_PER_SOL;
+    const rentExempt = await connection.getMinimumBalanceForRentExemption(StakeProgram.space);
+    const [voteAccount] = (await connection.getVoteAccounts()).current;
+
+    const tx = new Transaction()
+      .add(StakeProgram.createAccount({
+        fromPubkey: admin.publicKey,
+        stakePubkey: stakeAccount.publicKey,
+        authorized: new Authorized(admin.publicKey, admin.publicKey),
+        lamports: rentExempt + delegated,
+      }))
+      .add(StakeProgram.delegate({
+        stakePubkey: stakeAccount.publicKey,
+        authorizedPubkey: admin.publicKey,
+        votePubkey: new PublicKey(voteAccount.votePubkey),
+      }));
+    await provider.sendAndConfirm(tx, [stakeAccount]);
+
+    const record = findPda(program, [Buffer.from('validator_stake'), stakeAccount.publicKey.toBuffer()]);
+    await program.methods
+      .registerValidatorStakeAccount()
+      .accounts({
+        config: vaultSol.config,
+        validatorStakeRecord: record,
+        stakeAccount: stakeAccount.publicKey,
+        authority: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    expect((await program.account.validatorStakeRecord.fetch(record)).lastStake.toNumber()).to.equal(delegated);
+
+    // Epoch 0 can never be processed, so wait for the first epoch boundary
+    let { epoch } = await connection.getEpochInfo();
+    while (epoch === 0) {
+      await new Promise(resolve => setTimeout(resolve, 1000));
+      ({ epoch } = await connection.getEpochInfo());
+    }
+
+    const processAccounts = { config: vaultSol.config, authority: admin.publicKey };
+
+    // Every stake account must be followed by its record
+    await expectError(
+      program.methods
+        .processValidatorRewards([stakeAccount.publicKey], new anchor.BN(epoch))
+        .accounts(processAccounts)
+        .remainingAccounts([{ pubkey: stakeAccount.publicKey, isSigner: false, isWritable: false }])
+        .rpc(),
+      'InvalidStakeAccount'
+    );
+
+    const rateBefore = (await program.account.vaultConfig.fetch(vaultSol.config)).exchangeRateNumerator;
+    await program.methods
+      .processValidatorRewards([stakeAccount.publicKey], new anchor.BN(epoch))
+      .accounts(processAccounts)
+      .remainingAccounts([
+        { pubkey: stakeAccount.publicKey, isSigner: false, isWritable: false },
+        { pubkey: record, isSigner: false, isWritable: true },
+      ])
+      .rpc();
+
+    const config = await program.account.vaultConfig.fetch(vaultSol.config);
+    expect(config.exchangeRateNumerator.eq(rateBefore)).to.be.true;
+    expect(config.lastProcessedEpoch.toNumber()).to.equal(epoch);
+  });
+});
+
+describe('vault-sol custom reward schedules', () => {
+  const program = anchor.workspace.VaultSol as Program;
+  const admin = provider.wallet;
+
+  let vaultSol: VaultSolAccounts;
+
+  before(async () => {
+    vaultSol = await ensureVaultSol();
+  });
+
+  const setSchedule = (user: PublicKey, start: number, end: number) =>
+    program.methods
+      .setCustomRewardScheduleForUser(user, new anchor.BN(10), new anchor.BN(start), new anchor.BN(end))
+      .accounts({
+        config: vaultSol.config,
+        rewardsPool: vaultSol.rewardsPool,
+        customRewardSchedule: findPda(program, [Buffer.from('custom_reward_schedule'), user.toBuffer()]),
+        authority: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  it('Refuses to replace a schedule that is still accruing', async () => {
+    const user = Keypair.generate().publicKey;
+    const now = Math.floor(Date.now() / 1000);
+
+    await setSchedule(user, now - 60, now + 3600);
+    await expectError(setSchedule(user, now, now + 7200), 'CustomRewardScheduleActive');
+
+    const schedule = await program.account.customUserRewardSchedule.fetch(
+      findPda(program, [Buffer.from('custom_reward_schedule'), user.toBuffer()])
+    );
+    expect(schedule.endTimestamp.toNumber()).to.equal(now + 3600);
+  });
+
+  it('Refuses to replace an ended schedule whose bonus is unclaimed', async () => {
+    const user = Keypair.generate().publicKey;
+    const now = Math.floor(Date.now() / 1000);
+
+    await setSchedule(user, now - 120, now - 60);
+    await expectError(setSchedule(user, now, now + 60), 'CustomRewardScheduleActive');
+
+    // Removing the grant forfeits its bonus and frees the slot for a new one
+    await program.methods
+      .removeCustomRewardSchedule()
+      .accounts({
+        config: vaultSol.config,
+        customRewardSchedule: findPda(program, [Buffer.from('custom_reward_schedule'), user.toBuffer()]),
+        authority: admin.publicKey,
+      })
+      .rpc();
+    await setSchedule(user, now, now + 60);
+  });
+});
+
+describe('vault-sol global pause', () => {
+  const program = anchor.workspace.VaultSol as Program;
+  const admin = provider.wallet;
+
+  let vaultSol: VaultSolAccounts;
+
+  before(async () => {
+    vaultSol = await ensureVaultSol();
+  });
+
+  afterEach(async () => {
+    await setGlobalPause(false);
+  });
+
+  const createStake = (user: Keypair) =>
+    program.methods
+      .createStake(new anchor.BN(VAULT_SOL_MIN_STAKE))
+      .accounts({
+        config: vaultSol.config,
+        globalPause: vaultSol.globalPause,
+        stakePosition: findPda(program, [Buffer.from('stake_position'), user.publicKey.toBuffer()]),
+        userTier: null,
+        userPositionCounter: findPda(program, [Buffer.from('user_position_counter'), user.publicKey.toBuffer()]),
+        protocolStats: vaultSol.protocolStats,
+        user: user.publicKey,
+        solVault: vaultSol.solVault,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+  it('Only the guardian can pause the protocol', async () => {
+    const outsider = await fundedKeypair();
+    await expectError(
+      program.methods
+        .setGlobalPause(true)
+        .accounts({ globalPause: vaultSol.globalPause, guardian: outsider.publicKey })
+        .signers([outsider])
+        .rpc(),
+      'ConstraintHasOne'
+    );
+  });
+
+  it('Blocks staking while the protocol is paused', async () => {
+    const user = await fundedKeypair();
+
+    await setGlobalPause(true);
+    await expectError(createStake(user), 'GloballyPaused');
+
+    await setGlobalPause(false);
+    await createStake(user);
+    const position = await program.account.stakePosition.fetch(
+      findPda(program, [Buffer.from('stake_position'), user.publicKey.toBuffer()])
+    );
+    expect(position.amount.toNumber()).to.equal(VAULT_SOL_MIN_STAKE);
+  });
+});
+
+describe('vault-sol KYC tier deposit caps', () => {
+  const program = anchor.workspace.VaultSol as Program;
+  const admin = provider.wallet;
+
+  const UNCAPPED = new anchor.BN('18446744073709551615');
+  const TIER_0_CAP = 1 * LAMPORTS_PER_SOL;
+  const DEPOSIT = 0.6 * LAMPORTS_PER_SOL;
+
+  let vaultSol: VaultSolAccounts;
+  let vsolMint: PublicKey;
+
+  const setTierDepositCaps = (caps: anchor.BN[]) =>
+    program.methods
+      .setTierDepositCaps(caps)
+      .accounts({ config: vaultSol.config, authority: admin.publicKey })
+      .rpc();
+
+  const userTierPda = (user: PublicKey) => findPda(program, [Buffer.from('user_tier'), user.toBuffer()]);
+
+  const stakeSol = (user: Keypair, userVsolAccount: PublicKey, userTier: PublicKey | null) =>
+    program.methods
+      .stakeSol(new anchor.BN(DEPOSIT))
+      .accounts({
+        config: vaultSol.config,
+        globalPause: vaultSol.globalPause,
+        userPosition: findPda(program, [Buffer.from('user_position'), user.publicKey.toBuffer()]),
+        userTier,
+        userPositionCounter: findPda(program, [Buffer.from('user_position_counter'), user.publicKey.toBuffer()]),
+        protocolStats: vaultSol.protocolStats,
+        user: user.publicKey,
+        vsolMint,
+        userVsolAccount,
+        solVault: vaultSol.solVault,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([user])
+      .rpc();
+
+  before(async () => {
+    vaultSol = await ensureVaultSol();
+    vsolMint = await createMint(vaultSol.config, 9);
+    await setTierDepositCaps([
+      new anchor.BN(TIER_0_CAP),
+      new anchor.BN(100 * LAMPORTS_PER_SOL),
+      UNCAPPED,
+      UNCAPPED,
+    ]);
+  });
+
+  after(async () => {
+    await setTierDepositCaps([UNCAPPED, UNCAPPED, UNCAPPED, UNCAPPED]);
+  });
+
+  it('Applies the cap to the position total, not each deposit', async () => {
+    const user = await fundedKeypair(3);
+    const userVsolAccount = await createTokenAccount(vsolMint, user.publicKey);
+
+    await stakeSol(user, userVsolAccount, null);
+    await expectError(stakeSol(user, userVsolAccount, null), 'TierDepositCapExceeded');
+  });
+
+  it('Applies the higher cap of a verified tier', async () => {
+    const user = await fundedKeypair(3);
+    const userVsolAccount = await createTokenAccount(vsolMint, user.publicKey);
+
+    await program.methods
+      .setUserTier(user.publicKey, 1)
+      .accounts({
+        config: vaultSol.config,
+        userTier: userTierPda(user.publicKey),
+        authority: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await stakeSol(user, userVsolAccount, userTierPda(user.publicKey));
+    await stakeSol(user, userVsolAccount, userTierPda(user.publicKey));
+
+    const position = await program.account.userPosition.fetch(
+      findPda(program, [Buffer.from('user_position'), user.publicKey.toBuffer()])
+    );
+    expect(position.amountStaked.toNumber()).to.equal(2 * DEPOSIT);
+  });
+});
+
+describe('vault-sol protocol fee limit', () => {
+  const program = anchor.workspace.VaultSol as Program;
+  const admin = provider.wallet;
+
+  const MAX_PLATFORM_FEE_BPS = 1000;
+
+  let vaultSol: VaultSolAccounts;
+
+  const setMaxPoolFeeBps = (maxPoolFeeBps: number) =>
+    program.methods
+      .setMaxPoolFeeBps(maxPoolFeeBps)
+      .accounts({ config: vaultSol.config, authority: admin.publicKey })
+      .rpc();
+
+  before(async () => {
+    vaultSol = await ensureVaultSol();
+  });
+
+  after(async () => {
+    await setMaxPoolFeeBps(MAX_PLATFORM_FEE_BPS);
+  });
+
+  it('Starts at the hard ceiling', async () => {
+    const config = await program.account.vaultConfig.fetch(vaultSol.config);
+    expect(config.maxPoolFeeBps).to.equal(MAX_PLATFORM_FEE_BPS);
+  });
+
+  it('Rejects a limit above the hard ceiling or below the vault fee', async () => {
+    await expectError(setMaxPoolFeeBps(MAX_PLATFORM_FEE_BPS + 1), 'InvalidFee');
+    await expectError(setMaxPoolFeeBps(VAULT_SOL_PLATFORM_FEE_BPS - 1), 'InvalidFee');
+  });
+
+  it('Lowers the limit for every program', async () => {
+    await setMaxPoolFeeBps(VAULT_SOL_PLATFORM_FEE_BPS);
+    const config = await program.account.vaultConfig.fetch(vaultSol.config);
+    expect(config.maxPoolFeeBps).to.equal(VAULT_SOL_PLATFORM_FEE_BPS);
+  });
+});
+
+describe('vault-sol claim cooldown', () => {
+  const program = anchor.workspace.VaultSol as Program;
+  const admin = provider.wallet;
+
+  const COOLDOWN_SECONDS = 3600;
+
+  let vaultSol: VaultSolAccounts;
+  let user: Keypair;
+  let stakePosition: PublicKey;
+
+  const setMinClaimInterval = (seconds: number) =>
+    program.methods
+      .setMinClaimInterval(new anchor.BN(seconds))
+      .accounts({ config: vaultSol.config, authority: admin.publicKey })
+      .rpc();
+
+  before(async () => {
+    vaultSol = await ensureVaultSol();
+    user = await fundedKeypair();
+    stakePosition = findPda(program, [Buffer.from('stake_position'), user.publicKey.toBuffer()]);
+
+    await program.methods
+      .createStake(new anchor.BN(VAULT_SOL_MIN_STAKE))
+      .accounts({
+        config: vaultSol.config,
+        globalPause: vaultSol.globalPause,
+        stakePosition,
+        userTier: null,
+        userPositionCounter: findPda(program, [Buffer.from('user_position_counter'), user.publicKey.toBuffer()]),
+        protocolStats: vaultSol.protocolStats,
+        user: user.publicKey,
+        solVault: vaultSol.solVault,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    await setMinClaimInterval(COOLDOWN_SECONDS);
+    // Rewards need at least a second of accrual
+    await new Promise(resolve => setTimeout(resolve, 2000));
+  });
+
+  after(async () => {
+    await setMinClaimInterval(0);
+  });
+
+  it('Rejects an external claim within the cooldown', async () => {
+    await expectError(
+      program.methods
+        .claimRewards()
+        .accounts({
+          config: vaultSol.config,
+          globalPause: vaultSol.globalPause,
+          stakePosition,
+          rewardsPool: vaultSol.rewardsPool,
+          customRewardSchedule: null,
+          protocolStats: vaultSol.protocolStats,
+          user: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc(),
+      'ClaimCooldownActive'
+    );
+  });
+
+  it('Compounds within the same cooldown', async () => {
+    await program.methods
+      .compoundRewards()
+      .accounts({
+        config: vaultSol.config,
+        globalPause: vaultSol.globalPause,
+        stakePosition,
+        rewardsPool: vaultSol.rewardsPool,
+        customRewardSchedule: null,
+        userTier: null,
+        protocolStats: vaultSol.protocolStats,
+        solVault: vaultSol.solVault,
+        user: user.publicKey,
+      })
+      .signers([user])
+      .rpc();
+
+    const position = await program.account.stakePosition.fetch(stakePosition);
+    expect(position.amount.toNumber()).to.be.at.least(VAULT_SOL_MIN_STAKE);
+  });
+});
+
+describe('vault-sol reward runway', () => {
+  const program = anchor.workspace.VaultSol as Program;
+
+  const REWARD_RUNWAY_VIEW_VERSION = 1;
+  const SECONDS_PER_YEAR = 365 * 24 * 60 * 60;
+  const U64_MAX = new anchor.BN('18446744073709551615');
+
+  let vaultSol: VaultSolAccounts;
+
+  before(async () => {
+    vaultSol = await ensureVaultSol();
+  });
+
+  it('Returns the seconds of reserve left at the base emission rate', async () => {
+    const config = await program.account.vaultConfig.fetch(vaultSol.config);
+    const rewardsPool = await program.account.rewardsPool.fetch(vaultSol.rewardsPool);
+
+    const { version, payload } = await simulateView(
+      program.methods
+        .getRewardRunway()
+        .accounts({ config: vaultSol.config, rewardsPool: vaultSol.rewardsPool })
+    );
+
+    expect(version).to.equal(REWARD_RUNWAY_VIEW_VERSION);
+    expect(payload.length).to.equal(8);
+
+    const reserve = rewardsPool.totalRewards.sub(rewardsPool.distributedRewards);
+    const emissionPerSecond = config.totalStaked
+      .mul(new anchor.BN(rewardsPool.apyPoints))
+      .div(new anchor.BN(SECONDS_PER_YEAR * 10000));
+    const expected = emissionPerSecond.isZero() ? U64_MAX : reserve.div(emissionPerSecond);
+    expect(new anchor.BN(payload, 'le').eq(expected)).to.be.true;
+  });
+});